@@ -0,0 +1,89 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::load_config;
+use crate::init::resolve_fpath_dirs;
+use crate::order::resolve_order;
+use crate::paths::{Paths, paths};
+use crate::sync::resolve::resolve_source_files;
+use crate::template::{effective_templates, render_plugin_lines};
+
+/// Path to the compiled init script cached under `p.cache`, consumed by
+/// `rz init` when present (see [`crate::init::cmd_init`]).
+pub fn compiled_init_path(p: &Paths) -> PathBuf {
+    p.cache.join("init.zsh")
+}
+
+/// Render every plugin's `apply` templates (see [`crate::template`]) into a
+/// single cached script, in the effective load order (see
+/// [`crate::order`]), with `fpath`-type plugins' directories prepended at
+/// the top (see [`resolve_fpath_dirs`]).
+///
+/// Each plugin's lines are rendered against its content directory (see
+/// [`crate::config::Plugin::content_dir`] — cloned under `p.repos` by
+/// [`crate::sync::cmd_sync`] for most sources, or the given directory
+/// directly for `source = "local"`; `rz sync` must run, at least once,
+/// before `rz compile`, except for local plugins) and, for `each = true`
+/// templates, its resolved source file(s) — every file matched by its `use`
+/// glob patterns, or a single fallback-resolved file with none set (see
+/// [`resolve_source_files`]).
+/// Plugins that haven't been synced yet, or whose `apply` templates render
+/// no lines (e.g. a `source` template but no matching file), are skipped.
+///
+/// This trades `rz init`'s N individual lines at shell startup for one
+/// `source` call against the compiled file.
+///
+/// # Errors
+/// - Returns an error if the plugin order can't be resolved.
+/// - Returns an error if the compiled file can't be written.
+pub fn cmd_compile() -> Result<()> {
+    let cfg = load_config()?;
+    let p = paths()?;
+
+    let order = resolve_order(&cfg.plugins).context("failed to resolve plugin order")?;
+    let fpath_dirs = resolve_fpath_dirs(&cfg, &p);
+    let templates = effective_templates(&cfg.templates);
+
+    let mut script = String::from("# rat-zsh compiled init (generated by `rz compile`)\n");
+    if !fpath_dirs.is_empty() {
+        let quoted: Vec<String> = fpath_dirs.iter().map(|s| format!("\"{s}\"")).collect();
+        script.push_str(&format!("fpath=({} $fpath)\n", quoted.join(" ")));
+    }
+
+    let mut compiled_count = 0usize;
+    for idx in order {
+        let pl = &cfg.plugins[idx];
+        let slug = pl.slug();
+        let plug_name = pl.name.as_deref().unwrap_or(&slug);
+        let repo_dir = pl.content_dir(&p);
+        if !repo_dir.is_dir() {
+            // Not synced yet; skip rather than fail the whole compile.
+            continue;
+        }
+
+        let files: Vec<PathBuf> =
+            resolve_source_files(&repo_dir, pl.file.as_deref(), &pl.r#use, &cfg.plugin_filter)
+                .unwrap_or_default();
+
+        let apply = pl.apply_templates();
+        let lines = render_plugin_lines(&apply, &templates, &repo_dir, &files, plug_name);
+        if lines.is_empty() {
+            continue;
+        }
+
+        script.push_str(&format!("# --- {plug_name} ---\n"));
+        for line in lines {
+            script.push_str(&line);
+            script.push('\n');
+        }
+        compiled_count += 1;
+    }
+
+    fs::create_dir_all(&p.cache)?;
+    let dest = compiled_init_path(&p);
+    fs::write(&dest, &script).with_context(|| format!("failed to write {}", dest.display()))?;
+
+    println!("compiled {compiled_count} plugin(s) into {}", dest.display());
+    Ok(())
+}
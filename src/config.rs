@@ -1,8 +1,12 @@
 use anyhow::{Context, Result};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
 
-use crate::paths::{Paths, paths};
+use crate::git::GitReference;
+use crate::paths::{Paths, expand_tilde, paths};
+use crate::template::Template;
 
 /// Top-level configuration structure loaded from `config.toml`.
 ///
@@ -21,6 +25,143 @@ use crate::paths::{Paths, paths};
 pub struct Config {
     #[serde(default)]
     pub plugins: Vec<Plugin>,
+    /// Default shallow-clone depth applied to plugins that don't set their
+    /// own `depth` (see [`Plugin::depth`]). With `None`, plugins that track
+    /// the default branch shallow-clone to depth 1 and all others fetch full
+    /// history.
+    #[serde(default)]
+    pub default_depth: Option<u32>,
+    /// Where `rz upgrade` fetches releases from. See [`UpgradeConfig`].
+    #[serde(default)]
+    pub upgrade: UpgradeConfig,
+    /// User-defined shortcuts mapping a name to a sequence of `rz`
+    /// subcommands, expanded and run in order when the name is used in
+    /// place of a real subcommand.
+    ///
+    /// Example TOML:
+    /// ```toml
+    /// [aliases]
+    /// refresh = ["sync", "compile"]
+    /// ```
+    #[serde(default)]
+    pub aliases: HashMap<String, Vec<String>>,
+    /// Named zsh-code templates available to plugins' `apply` lists (see
+    /// [`crate::template`]), merged over the built-ins of the same names
+    /// (`source`, `PATH`, `FPATH`, `path`, `fpath`) — same name overrides,
+    /// new names add.
+    ///
+    /// Example TOML:
+    /// ```toml
+    /// [templates]
+    /// defer = { value = 'zsh-defer source "{{ file }}"', each = true }
+    /// ```
+    #[serde(default)]
+    pub templates: HashMap<String, Template>,
+    /// Patterns protecting hand-maintained entries in `plugins`/`repos` from
+    /// `rz sync`'s cleanup step. See [`CleanupConfig`].
+    #[serde(default)]
+    pub cleanup: CleanupConfig,
+    /// Worker-thread count for parallel stale-repo cleanup (see
+    /// [`crate::sync::cleanup::cleanup_stale_repos_parallel`]). Defaults to
+    /// the detected CPU count when unset.
+    #[serde(default)]
+    pub jobs: Option<usize>,
+    /// Extension allow/deny rules for filtering which files count as loadable
+    /// plugin content. See [`PluginFilterConfig`].
+    ///
+    /// Named `[plugin_filter]` rather than `[plugins]` to avoid colliding
+    /// with the `[[plugins]]` array-of-tables above.
+    #[serde(default)]
+    pub plugin_filter: PluginFilterConfig,
+}
+
+/// Configuration for `rz upgrade`'s release source.
+///
+/// Example TOML:
+/// ```toml
+/// [upgrade]
+/// source = "gitlab"
+/// repo   = "some-group/rat-zsh-fork"
+/// ```
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct UpgradeConfig {
+    /// `"github"` (default) or `"gitlab"`.
+    #[serde(default)]
+    pub source: String,
+    /// `<owner>/<repo>` (GitHub) or `<group>/<project>` (GitLab), possibly
+    /// with nested subgroups. Defaults to `"gotokazuki/rat-zsh"` when empty.
+    #[serde(default)]
+    pub repo: String,
+}
+
+/// Configuration for `rz sync`'s cleanup step.
+///
+/// Example TOML:
+/// ```toml
+/// [cleanup]
+/// keep = ["local-*", "**/my-dev-plugin"]
+/// ```
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct CleanupConfig {
+    /// Gitignore-style patterns (see [`crate::sync::keep_patterns::KeepMatcher`])
+    /// matched against each entry's name inside `plugins`/`repos`. A match
+    /// protects the entry from deletion even if it isn't otherwise expected,
+    /// exactly as if it had an `expect` hit.
+    #[serde(default)]
+    pub keep: Vec<String>,
+}
+
+/// Extension allow/deny rules for filtering which files in a plugin's
+/// content directory count as loadable plugin files, rather than stray
+/// `.md`/`.zwc`/editor-backup files or VCS metadata.
+///
+/// Example TOML:
+/// ```toml
+/// [plugin_filter]
+/// allowed_extensions = ["zsh", "sh"]
+/// excluded_extensions = ["zwc", "md"]
+/// ```
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct PluginFilterConfig {
+    /// If non-empty, only files whose extension (case-insensitively) is in
+    /// this set are considered loadable; all others are rejected.
+    #[serde(default)]
+    pub allowed_extensions: Vec<String>,
+    /// Files whose extension (case-insensitively) is in this set are never
+    /// considered loadable, even if `allowed_extensions` would otherwise
+    /// accept them.
+    #[serde(default)]
+    pub excluded_extensions: Vec<String>,
+}
+
+impl PluginFilterConfig {
+    /// Is `file_name` (a final path component, e.g. from [`std::path::Path::file_name`])
+    /// loadable under these rules?
+    ///
+    /// Extensions are compared case-insensitively. A file with no extension
+    /// (e.g. a symlink named after its plugin, with no `.ext` suffix) is
+    /// always loadable, regardless of `allowed_extensions`/`excluded_extensions`.
+    pub fn is_extension_allowed(&self, file_name: &str) -> bool {
+        let Some(ext) = Path::new(file_name)
+            .extension()
+            .and_then(|e| e.to_str())
+        else {
+            return true;
+        };
+        let excluded = self
+            .excluded_extensions
+            .iter()
+            .any(|e| e.eq_ignore_ascii_case(ext));
+        if excluded {
+            return false;
+        }
+        if self.allowed_extensions.is_empty() {
+            return true;
+        }
+        self.allowed_extensions
+            .iter()
+            .any(|e| e.eq_ignore_ascii_case(ext))
+    }
 }
 
 /// Representation of a single plugin entry in `config.toml`.
@@ -33,16 +174,194 @@ pub struct Plugin {
     pub source: String,
     #[serde(default)]
     pub repo: String,
+    /// Local directory to use as this plugin's content, for `source =
+    /// "local"` plugins. A leading `~` is expanded to the home directory.
+    /// `repo` is unused (and conventionally left empty) for local plugins;
+    /// see [`Plugin::slug`]/[`Plugin::content_dir`].
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Explicit branch to track. Takes precedence over `tag` and `rev`.
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// Explicit tag to pin to. Takes precedence over `rev`.
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// Legacy loose revision string (branch, tag, or commit — resolved in
+    /// that order). Prefer `branch`/`tag` for new configs; see [`Plugin::git_reference`].
     #[serde(default)]
     pub rev: Option<String>,
     #[serde(default)]
     pub file: Option<String>,
+    /// Glob patterns matching every file this plugin should source, for
+    /// plugins that ship more than one (e.g. `lib/*.zsh`). Supports `*`,
+    /// `?`, and `{a,b}` brace alternation; a pattern containing `/` matches
+    /// within that one subdirectory. Matches across all patterns are
+    /// deduplicated and sorted for a stable order. Takes precedence over
+    /// `file` when non-empty; see [`crate::sync::resolve::resolve_source_files`].
+    #[serde(default, rename = "use")]
+    pub r#use: Vec<String>,
     #[serde(default)]
     pub r#type: Option<String>,
     #[serde(default)]
     pub name: Option<String>,
     #[serde(default)]
     pub fpath_dirs: Vec<String>,
+    /// When `true` (only meaningful for `type = "fpath"` plugins), also scan
+    /// the plugin's repo for zsh completion files (`_name`) and add every
+    /// directory containing at least one to the effective fpath, alongside
+    /// any explicit `fpath_dirs` entries. See
+    /// [`crate::init::resolve_fpath_dirs`].
+    #[serde(default)]
+    pub autodetect: bool,
+    /// If non-empty, this plugin is only active when the current machine's
+    /// hostname (via the `hostname` crate) is one of these. Lets one shared
+    /// `config.toml` drive multiple machines. See [`crate::sync::jobs::build_jobs`].
+    #[serde(default)]
+    pub hosts: Vec<String>,
+    /// If non-empty, this plugin is inactive when the current machine's
+    /// hostname matches one of these, even if `hosts` would otherwise
+    /// include it. Evaluated after `hosts`.
+    #[serde(default)]
+    pub not_hosts: Vec<String>,
+    /// If non-empty, this plugin is only active when `std::env::consts::OS`
+    /// (e.g. `"linux"`, `"macos"`, `"windows"`) is one of these.
+    #[serde(default)]
+    pub os: Vec<String>,
+    /// Named templates (see [`crate::template`]) to render for this plugin
+    /// when compiling the init script (`rz compile`). Defaults (when unset)
+    /// to `["source"]`, or `["fpath"]` for `type = "fpath"` plugins — see
+    /// [`Plugin::apply_templates`].
+    #[serde(default)]
+    pub apply: Option<Vec<String>>,
+    /// Shallow-clone depth for this plugin's repo. Overrides [`Config::default_depth`].
+    /// When neither this nor `default_depth` is set, plugins with no pinned
+    /// `branch`/`tag`/`rev` (and no `config.lock` entry) shallow-clone to
+    /// depth 1, and all others fetch full history.
+    #[serde(default)]
+    pub depth: Option<u32>,
+    /// Explicit sort priority for breaking ties between plugins that have no
+    /// remaining `after`/`before` dependencies on each other. Lower values
+    /// load earlier; unset defaults to `0`, except for the built-in
+    /// `zsh-users/zsh-autosuggestions`/`zsh-users/zsh-syntax-highlighting`
+    /// tail plugins, which default to a high value so they keep loading
+    /// last unless a config sets its own `priority` (even `0`) for them.
+    /// Used by [`crate::order`] to compute the effective load order.
+    #[serde(default)]
+    pub priority: Option<i64>,
+    /// Plugins that must be sourced before this one. Each entry references
+    /// another plugin by its `name` (if set) or `repo`. Used by
+    /// [`crate::order`] to compute the effective load order.
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Plugins that must be sourced after this one. Each entry references
+    /// another plugin by its `name` (if set) or `repo`. Used by
+    /// [`crate::order`] to compute the effective load order.
+    #[serde(default)]
+    pub before: Vec<String>,
+}
+
+impl Plugin {
+    /// Resolve this plugin's configured checkout target into a typed
+    /// [`GitReference`].
+    ///
+    /// `branch` and `tag` express intent explicitly and take priority; the
+    /// legacy `rev` field is kept as a back-compat fallback that maps to
+    /// [`GitReference::Rev`]. With none set, the plugin tracks the remote's
+    /// default branch.
+    pub fn git_reference(&self) -> GitReference {
+        if let Some(b) = &self.branch {
+            GitReference::Branch(b.clone())
+        } else if let Some(t) = &self.tag {
+            GitReference::Tag(t.clone())
+        } else if let Some(r) = &self.rev {
+            GitReference::Rev(r.clone())
+        } else {
+            GitReference::Default
+        }
+    }
+
+    /// Resolve this plugin's effective list of templates to render into the
+    /// compiled init script (see [`crate::template::render_plugin_lines`]).
+    ///
+    /// Defaults to `["source"]`, or `["fpath"]` for `type = "fpath"`
+    /// plugins, preserving their behavior from before the template engine
+    /// existed, when `apply` isn't set.
+    pub fn apply_templates(&self) -> Vec<String> {
+        self.apply.clone().unwrap_or_else(|| {
+            if self.r#type.as_deref() == Some("fpath") {
+                vec!["fpath".to_string()]
+            } else {
+                vec!["source".to_string()]
+            }
+        })
+    }
+
+    /// Whether this plugin's content comes from a local directory (`source
+    /// = "local"`) rather than being cloned from a remote. Local plugins
+    /// skip `rz sync`'s clone/fetch step entirely and point straight at
+    /// `path`, and are exempt from `config.lock` (see [`crate::lock`]),
+    /// since there's no commit to pin.
+    pub fn is_local(&self) -> bool {
+        self.source == "local"
+    }
+
+    /// This plugin's stable on-disk identifier, used for both its directory
+    /// under `p.repos` (see [`Plugin::content_dir`]) and its default link
+    /// name under `p.plugins`.
+    ///
+    /// - `source = "local"`: the final path component of `path`.
+    /// - `source = "git"` (an arbitrary clone URL in `repo`, for GitLab,
+    ///   Bitbucket, or self-hosted forges with no named shorthand): the
+    ///   final path segment of that URL, with a trailing `.git` stripped.
+    /// - otherwise: `repo` (the `owner/repo` GitHub/GitLab/etc. shorthand)
+    ///   with `/` replaced by `__`.
+    pub fn slug(&self) -> String {
+        if self.is_local() {
+            expand_tilde(self.path.as_deref().unwrap_or(""))
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("local")
+                .to_string()
+        } else if self.source == "git" {
+            last_path_segment(&self.repo)
+        } else {
+            self.repo.replace('/', "__")
+        }
+    }
+
+    /// This plugin's content directory: `path` (expanded) for `source =
+    /// "local"` plugins, which are never cloned, or `p.repos.join(self.slug())`
+    /// for every other source kind.
+    pub fn content_dir(&self, p: &Paths) -> PathBuf {
+        if self.is_local() {
+            expand_tilde(self.path.as_deref().unwrap_or(""))
+        } else {
+            p.repos.join(self.slug())
+        }
+    }
+
+    /// This plugin's human-readable display name: `name` if set, else
+    /// `repo` for `owner/repo`-shorthand sources, else [`Plugin::slug`] for
+    /// `local`/`git` sources whose `repo`/`path` aren't human-friendly on
+    /// their own.
+    pub fn display_name(&self) -> String {
+        if let Some(n) = &self.name {
+            n.clone()
+        } else if self.is_local() || self.source == "git" {
+            self.slug()
+        } else {
+            self.repo.clone()
+        }
+    }
+}
+
+/// Extract the final path segment from a URL or SCP-like path
+/// (`git@host:owner/repo.git` or `https://host/owner/repo.git`), stripping
+/// a trailing `.git`. Used by [`Plugin::slug`] for `source = "git"` plugins.
+fn last_path_segment(url: &str) -> String {
+    let trimmed = url.trim_end_matches('/');
+    let last = trimmed.rsplit(['/', ':']).next().unwrap_or(trimmed);
+    last.strip_suffix(".git").unwrap_or(last).to_string()
 }
 
 /// Load and parse a configuration file from the given path.
@@ -116,4 +435,176 @@ mod tests {
         assert!(msg.contains("config not found"));
         assert!(msg.contains("nope.toml"));
     }
+
+    fn plugin(branch: Option<&str>, tag: Option<&str>, rev: Option<&str>) -> Plugin {
+        Plugin {
+            source: String::new(),
+            repo: String::new(),
+            path: None,
+            branch: branch.map(str::to_string),
+            tag: tag.map(str::to_string),
+            rev: rev.map(str::to_string),
+            file: None,
+            r#use: Vec::new(),
+            r#type: None,
+            name: None,
+            fpath_dirs: Vec::new(),
+            autodetect: false,
+            hosts: Vec::new(),
+            not_hosts: Vec::new(),
+            os: Vec::new(),
+            apply: None,
+            priority: None,
+            depth: None,
+            after: Vec::new(),
+            before: Vec::new(),
+        }
+    }
+
+    fn source_plugin(source: &str, repo: &str, path: Option<&str>, name: Option<&str>) -> Plugin {
+        Plugin {
+            source: source.to_string(),
+            repo: repo.to_string(),
+            path: path.map(str::to_string),
+            branch: None,
+            tag: None,
+            rev: None,
+            file: None,
+            r#use: Vec::new(),
+            r#type: None,
+            name: name.map(str::to_string),
+            fpath_dirs: Vec::new(),
+            autodetect: false,
+            hosts: Vec::new(),
+            not_hosts: Vec::new(),
+            os: Vec::new(),
+            apply: None,
+            priority: None,
+            depth: None,
+            after: Vec::new(),
+            before: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn slug_uses_repo_shorthand_by_default() {
+        let pl = source_plugin("github", "zsh-users/zsh-autosuggestions", None, None);
+        assert_eq!(pl.slug(), "zsh-users__zsh-autosuggestions");
+    }
+
+    #[test]
+    fn slug_derives_from_last_path_segment_for_git_source() {
+        let pl = source_plugin("git", "https://gitlab.com/owner/repo.git", None, None);
+        assert_eq!(pl.slug(), "repo");
+
+        let pl = source_plugin("git", "git@example.com:owner/repo.git", None, None);
+        assert_eq!(pl.slug(), "repo");
+    }
+
+    #[test]
+    fn slug_derives_from_path_basename_for_local_source() {
+        let pl = source_plugin("local", "", Some("/home/user/dev/my-plugin"), None);
+        assert_eq!(pl.slug(), "my-plugin");
+    }
+
+    #[test]
+    fn content_dir_points_at_path_for_local_source_without_joining_repos() {
+        let p = Paths {
+            bin: PathBuf::from("/home/user/.rz/bin"),
+            plugins: PathBuf::from("/home/user/.rz/plugins"),
+            repos: PathBuf::from("/home/user/.rz/repos"),
+            cache: PathBuf::from("/home/user/.rz/cache"),
+            config: PathBuf::from("/home/user/.rz/config.toml"),
+            lock: PathBuf::from("/home/user/.rz/config.lock"),
+        };
+        let pl = source_plugin("local", "", Some("/home/user/dev/my-plugin"), None);
+        assert_eq!(pl.content_dir(&p), PathBuf::from("/home/user/dev/my-plugin"));
+
+        let pl = source_plugin("github", "owner/repo", None, None);
+        assert_eq!(pl.content_dir(&p), PathBuf::from("/home/user/.rz/repos/owner__repo"));
+    }
+
+    #[test]
+    fn display_name_prefers_name_then_falls_back_by_source() {
+        let pl = source_plugin("github", "owner/repo", None, Some("myname"));
+        assert_eq!(pl.display_name(), "myname");
+
+        let pl = source_plugin("github", "owner/repo", None, None);
+        assert_eq!(pl.display_name(), "owner/repo");
+
+        let pl = source_plugin("local", "", Some("/home/user/dev/my-plugin"), None);
+        assert_eq!(pl.display_name(), "my-plugin");
+    }
+
+    #[test]
+    fn git_reference_prefers_branch_over_tag_and_rev() {
+        let pl = plugin(Some("main"), Some("v1.0.0"), Some("deadbeef"));
+        assert_eq!(pl.git_reference(), GitReference::Branch("main".to_string()));
+    }
+
+    #[test]
+    fn git_reference_prefers_tag_over_rev() {
+        let pl = plugin(None, Some("v1.0.0"), Some("deadbeef"));
+        assert_eq!(pl.git_reference(), GitReference::Tag("v1.0.0".to_string()));
+    }
+
+    #[test]
+    fn git_reference_falls_back_to_rev() {
+        let pl = plugin(None, None, Some("deadbeef"));
+        assert_eq!(pl.git_reference(), GitReference::Rev("deadbeef".to_string()));
+    }
+
+    #[test]
+    fn git_reference_defaults_when_unset() {
+        let pl = plugin(None, None, None);
+        assert_eq!(pl.git_reference(), GitReference::Default);
+    }
+
+    #[test]
+    fn extension_allowed_with_no_rules_accepts_everything() {
+        let cfg = PluginFilterConfig::default();
+        assert!(cfg.is_extension_allowed("foo.zsh"));
+        assert!(cfg.is_extension_allowed("foo.md"));
+        assert!(cfg.is_extension_allowed("foo"));
+    }
+
+    #[test]
+    fn extension_allowed_rejects_excluded_extensions_case_insensitively() {
+        let cfg = PluginFilterConfig {
+            allowed_extensions: Vec::new(),
+            excluded_extensions: vec!["zwc".to_string(), "md".to_string()],
+        };
+        assert!(!cfg.is_extension_allowed("compiled.ZWC"));
+        assert!(!cfg.is_extension_allowed("README.md"));
+        assert!(cfg.is_extension_allowed("plugin.zsh"));
+    }
+
+    #[test]
+    fn extension_allowed_with_allowlist_rejects_everything_else() {
+        let cfg = PluginFilterConfig {
+            allowed_extensions: vec!["zsh".to_string(), "sh".to_string()],
+            excluded_extensions: Vec::new(),
+        };
+        assert!(cfg.is_extension_allowed("plugin.zsh"));
+        assert!(cfg.is_extension_allowed("plugin.SH"));
+        assert!(!cfg.is_extension_allowed("README.md"));
+    }
+
+    #[test]
+    fn extension_allowed_excluded_list_wins_over_allowed_list() {
+        let cfg = PluginFilterConfig {
+            allowed_extensions: vec!["zsh".to_string()],
+            excluded_extensions: vec!["zsh".to_string()],
+        };
+        assert!(!cfg.is_extension_allowed("plugin.zsh"));
+    }
+
+    #[test]
+    fn extension_allowed_files_with_no_extension_always_pass() {
+        let cfg = PluginFilterConfig {
+            allowed_extensions: vec!["zsh".to_string()],
+            excluded_extensions: Vec::new(),
+        };
+        assert!(cfg.is_extension_allowed("my-plugin"));
+    }
 }
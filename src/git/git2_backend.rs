@@ -1,23 +1,171 @@
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Context, Result, anyhow, bail};
 use git2::{
-    BranchType, Cred, FetchOptions, ObjectType, Reference, RemoteCallbacks, Repository, ResetType,
+    BranchType, Cred, DescribeFormatOptions, DescribeOptions, FetchOptions, ObjectType,
+    Reference, RemoteCallbacks, Repository, RepositoryState, ResetType, StatusOptions,
     SubmoduleUpdateOptions,
     build::{CheckoutBuilder, RepoBuilder},
 };
 use std::path::Path;
 
-/// Build a `FetchOptions` with SSH-agent credentials enabled.
+/// A plugin's desired checkout target, expressed as explicit intent rather
+/// than a single overloaded string.
 ///
-/// This allows Git operations to authenticate using the user's SSH agent.
-/// If no SSH key is found, it falls back to default credentials.
-fn fetch_opts_with_creds() -> FetchOptions<'static> {
+/// `Branch` and `Tag` resolve deterministically to the matching remote
+/// branch or tag, respectively. `Rev` is the legacy loose form: it tries,
+/// in order, a local branch, a remote branch, a tag, then a raw revspec
+/// (commit SHA) — kept for configs that still set a bare `rev`. `Default`
+/// means "track the remote's default branch".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitReference {
+    Branch(String),
+    Tag(String),
+    Rev(String),
+    Default,
+}
+
+/// A single commit surfaced in a post-update changelog: a short SHA and the
+/// first line of its commit message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangelogEntry {
+    pub short_sha: String,
+    pub summary: String,
+}
+
+/// Outcome of [`ensure_repo`]'s update, describing what (if anything)
+/// changed so callers can print a changelog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepoUpdate {
+    /// The repository didn't exist yet and was freshly cloned; there's no
+    /// prior state to diff against.
+    Cloned,
+    /// The checked-out tip didn't move.
+    Unchanged,
+    /// The previous tip is an ancestor of the new tip: `commits` lists every
+    /// commit in between, oldest first.
+    Commits {
+        old_short: String,
+        new_short: String,
+        commits: Vec<ChangelogEntry>,
+    },
+    /// The previous tip is *not* an ancestor of the new tip (a force-update,
+    /// or a tag bump to an unrelated history) — the commit range can't be
+    /// walked meaningfully, so only the old/new short SHAs are reported.
+    Diverged { old_short: String, new_short: String },
+}
+
+/// Outcome of comparing a plugin's checked-out revision against its remote target.
+///
+/// Produced by [`update_status`] without mutating the working tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UpdateStatus {
+    /// Number of commits the local tip is ahead of the target.
+    pub ahead: usize,
+    /// Number of commits the local tip is behind the target.
+    pub behind: usize,
+    /// Whether the working tree has uncommitted changes.
+    pub dirty: bool,
+    /// Whether `HEAD` is detached (e.g. checked out at a tag or pinned commit).
+    pub detached: bool,
+    /// Set when the target revision could not be resolved, so ahead/behind are meaningless.
+    pub unknown: bool,
+}
+
+impl UpdateStatus {
+    /// `true` when the local tip matches the resolved target exactly.
+    pub fn up_to_date(&self) -> bool {
+        !self.unknown && self.ahead == 0 && self.behind == 0
+    }
+}
+
+/// Resolve the host portion of a repository URL, for token selection.
+///
+/// Handles both `https://host/...`/`http://host/...` URLs and
+/// `git@host:owner/repo` SCP-style SSH URLs.
+fn host_from_url(url: &str) -> Option<&str> {
+    if let Some(rest) = url.strip_prefix("git@") {
+        return rest.split(':').next();
+    }
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    rest.split('/').next()
+}
+
+/// Look up the HTTPS access token env var for a given host, mirroring
+/// `gh_client`'s `GITHUB_TOKEN` handling (see [`crate::upgrade::github`])
+/// for the other forges `rz` knows about.
+fn token_env_var_for_host(host: &str) -> Option<&'static str> {
+    if host == "github.com" {
+        Some("GITHUB_TOKEN")
+    } else if host == "gitlab.com" {
+        Some("GITLAB_TOKEN")
+    } else if host == "codeberg.org" || host.contains("gitea") || host.contains("forgejo") {
+        Some("GITEA_TOKEN")
+    } else if host == "bitbucket.org" {
+        Some("BITBUCKET_TOKEN")
+    } else {
+        None
+    }
+}
+
+/// Build a `FetchOptions` with credentials enabled for both HTTPS and SSH
+/// remotes.
+///
+/// libgit2 re-invokes the `credentials` callback on each failed attempt, so
+/// this tries each method **at most once**, in order, to avoid looping
+/// forever on a remote none of them can satisfy:
+/// 1. `Cred::ssh_key_from_agent`, when the URL allows `SSH_KEY` auth.
+/// 2. `Cred::userpass_plaintext`, when the URL allows
+///    `USER_PASS_PLAINTEXT` and a token env var is set — the host-specific
+///    one (`GITHUB_TOKEN`, `GITLAB_TOKEN`, `GITEA_TOKEN`, `BITBUCKET_TOKEN`,
+///    see [`token_env_var_for_host`]), falling back to the generic
+///    `RZ_GIT_TOKEN` for self-hosted forges with no named match.
+/// 3. The user's configured git credential helper (`Cred::credential_helper`).
+/// 4. `Cred::default()`, as a last resort.
+///
+/// When `depth` is `Some(n)`, the fetch/clone only retrieves the last `n`
+/// commits on each ref (a shallow operation); `None` retrieves full history.
+fn fetch_opts_with_creds(depth: Option<u32>) -> FetchOptions<'static> {
     let mut cb = RemoteCallbacks::new();
-    cb.credentials(|_url, username_from_url, _allowed| {
-        Cred::ssh_key_from_agent(username_from_url.unwrap_or("git")).or_else(|_| Cred::default())
+    let mut tried_ssh = false;
+    let mut tried_token = false;
+    let mut tried_helper = false;
+    cb.credentials(move |url, username_from_url, allowed| {
+        if allowed.contains(git2::CredentialType::SSH_KEY) && !tried_ssh {
+            tried_ssh = true;
+            if let Ok(cred) = Cred::ssh_key_from_agent(username_from_url.unwrap_or("git")) {
+                return Ok(cred);
+            }
+        }
+
+        if allowed.contains(git2::CredentialType::USER_PASS_PLAINTEXT) && !tried_token {
+            tried_token = true;
+            let token = host_from_url(url)
+                .and_then(token_env_var_for_host)
+                .and_then(|var| std::env::var(var).ok())
+                .or_else(|| std::env::var("RZ_GIT_TOKEN").ok());
+            if let Some(token) = token {
+                return Cred::userpass_plaintext(&token, "");
+            }
+        }
+
+        if !tried_helper {
+            tried_helper = true;
+            if let Ok(git_cfg) = git2::Config::open_default()
+                && let Ok(cred) = Cred::credential_helper(&git_cfg, url, username_from_url)
+            {
+                return Ok(cred);
+            }
+        }
+
+        Cred::default()
     });
 
     let mut fo = FetchOptions::new();
     fo.remote_callbacks(cb);
+    if let Some(d) = depth {
+        fo.depth(d as i32);
+    }
     fo
 }
 
@@ -38,30 +186,38 @@ fn update_submodules(repo: &Repository) -> Result<()> {
     Ok(())
 }
 
-/// Attach to the remote's default branch (origin/HEAD), creating a local
-/// tracking branch if necessary, and hard-reset to the remote tip.
+/// Resolve the remote-tracking ref name for the repository's default branch.
 ///
 /// Fallbacks are tried in order if `origin/HEAD` is missing:
 /// `refs/remotes/origin/main` → `refs/remotes/origin/master`.
 ///
 /// # Errors
-/// Returns an error if no suitable default branch can be found or checkout fails.
-fn attach_default_branch(repo: &Repository) -> Result<()> {
-    let target_remote_ref = if let Ok(origin_head) = repo.find_reference("refs/remotes/origin/HEAD")
-    {
-        origin_head
+/// Returns an error if no suitable default branch can be found.
+fn resolve_default_branch_ref(repo: &Repository) -> Result<String> {
+    if let Ok(origin_head) = repo.find_reference("refs/remotes/origin/HEAD") {
+        return origin_head
             .symbolic_target()
             .map(|s| s.to_string())
-            .ok_or_else(|| anyhow!("origin/HEAD has no symbolic target"))?
-    } else if repo.find_reference("refs/remotes/origin/main").is_ok() {
-        "refs/remotes/origin/main".to_string()
-    } else if repo.find_reference("refs/remotes/origin/master").is_ok() {
-        "refs/remotes/origin/master".to_string()
-    } else {
-        return Err(anyhow!(
-            "could not determine default branch (missing origin/HEAD, origin/main, origin/master)"
-        ));
-    };
+            .ok_or_else(|| anyhow!("origin/HEAD has no symbolic target"));
+    }
+    if repo.find_reference("refs/remotes/origin/main").is_ok() {
+        return Ok("refs/remotes/origin/main".to_string());
+    }
+    if repo.find_reference("refs/remotes/origin/master").is_ok() {
+        return Ok("refs/remotes/origin/master".to_string());
+    }
+    Err(anyhow!(
+        "could not determine default branch (missing origin/HEAD, origin/main, origin/master)"
+    ))
+}
+
+/// Attach to the remote's default branch (origin/HEAD), creating a local
+/// tracking branch if necessary, and hard-reset to the remote tip.
+///
+/// # Errors
+/// Returns an error if no suitable default branch can be found or checkout fails.
+fn attach_default_branch(repo: &Repository) -> Result<()> {
+    let target_remote_ref = resolve_default_branch_ref(repo)?;
 
     let branch_name = target_remote_ref
         .strip_prefix("refs/remotes/origin/")
@@ -88,60 +244,177 @@ fn attach_default_branch(repo: &Repository) -> Result<()> {
     Ok(())
 }
 
+/// Refuse to touch a repository that's mid-merge/rebase/cherry-pick/revert/
+/// bisect, mirroring the `Repository::state()` check starship's `Context`
+/// uses before reading repo state.
+///
+/// # Errors
+/// Returns an error naming the in-progress operation if `repo.state()` isn't
+/// [`RepositoryState::Clean`].
+fn ensure_clean_repo_state(repo: &Repository) -> Result<()> {
+    let state = repo.state();
+    if state != RepositoryState::Clean {
+        bail!(
+            "repository at {} has an in-progress {:?}; resolve or abort it before syncing",
+            repo.path().display(),
+            state
+        );
+    }
+    Ok(())
+}
+
 /// Ensure that a repository exists at the given path.
 ///
 /// - If the repository already exists:
 ///   - Performs `git fetch origin`
-///   - If `rev` is Some: checkout that revision (branch→attach / tag・SHA→detached)
-///   - If `rev` is None: **attach to the remote's default branch** (origin/HEAD)
+///   - Resolves `reference` deterministically (see [`GitReference`]) and checks it out
 ///   - Updates submodules
 ///
 /// - If the repository does not exist:
 ///   - Clones it from the given URL
-///   - If `rev` is Some: checkout that revision
-///   - If `rev` is None: **attach to the remote's default branch**
+///   - Resolves `reference` and checks it out
 ///   - Updates submodules
 ///
+/// `depth` requests a shallow clone/fetch (only the last `n` commits per
+/// ref). If `reference` can't be resolved in the shallow history, the
+/// repository is transparently unshallowed (refetched with full depth)
+/// and the checkout is retried once before giving up.
+///
+/// Before touching an existing repository, refuses to proceed if it's
+/// mid-merge/rebase/cherry-pick (see [`ensure_clean_repo_state`]), and after
+/// checking out `reference` verifies the working tree landed back in a
+/// clean state, rather than silently leaving a half-finished operation
+/// behind.
+///
+/// Returns a [`RepoUpdate`] describing what changed, for callers that want
+/// to print a changelog.
+///
 /// # Errors
-/// Returns an error if cloning, fetching, or checkout fails.
-pub fn ensure_repo(url: &str, dest: &Path, rev: Option<&str>) -> Result<()> {
+/// Returns an error if the repository is mid-merge/rebase/cherry-pick, or if
+/// cloning, fetching, or checkout fails.
+pub fn ensure_repo(
+    url: &str,
+    dest: &Path,
+    reference: &GitReference,
+    depth: Option<u32>,
+) -> Result<RepoUpdate> {
     if dest.join(".git").exists() {
         let repo = Repository::open(dest)?;
-        fetch_origin(&repo)?;
-        if let Some(r) = rev {
-            checkout_rev(&repo, r)?;
-        } else {
-            attach_default_branch(&repo)?;
-        }
+        ensure_clean_repo_state(&repo)?;
+        let old_oid = repo.head().ok().and_then(|h| h.target());
+
+        fetch_origin(&repo, depth)?;
+        checkout_reference_unshallowing(&repo, reference, depth)?;
+        ensure_clean_repo_state(&repo)
+            .context("working tree did not end in a clean state after checkout")?;
         update_submodules(&repo)?;
-        Ok(())
+
+        let new_oid = repo.head()?.target();
+        Ok(summarize_update(&repo, old_oid, new_oid))
     } else {
         let mut builder = RepoBuilder::new();
-        builder.fetch_options(fetch_opts_with_creds());
+        builder.fetch_options(fetch_opts_with_creds(depth));
 
         let repo = builder
             .clone(url, dest)
             .with_context(|| format!("git clone {}", url))?;
 
-        if let Some(r) = rev {
-            checkout_rev(&repo, r)?;
-        } else {
-            fetch_origin(&repo)?;
-            attach_default_branch(&repo)?;
+        if *reference == GitReference::Default {
+            fetch_origin(&repo, depth)?;
         }
+        checkout_reference_unshallowing(&repo, reference, depth)?;
         update_submodules(&repo)?;
-        Ok(())
+        Ok(RepoUpdate::Cloned)
+    }
+}
+
+/// Build a [`RepoUpdate`] describing the transition from `old_oid` to
+/// `new_oid`.
+///
+/// If `old_oid` is an ancestor of `new_oid`, walks the commit range with a
+/// `Revwalk` (hiding `old_oid`, pushing `new_oid`) to list every commit in
+/// between. Otherwise (force-update, or a tag bump to unrelated history)
+/// falls back to reporting just the old/new short SHAs.
+fn summarize_update(
+    repo: &Repository,
+    old_oid: Option<git2::Oid>,
+    new_oid: Option<git2::Oid>,
+) -> RepoUpdate {
+    let (Some(old_oid), Some(new_oid)) = (old_oid, new_oid) else {
+        return RepoUpdate::Unchanged;
+    };
+    if old_oid == new_oid {
+        return RepoUpdate::Unchanged;
+    }
+
+    if !matches!(repo.graph_descendant_of(new_oid, old_oid), Ok(true)) {
+        return RepoUpdate::Diverged {
+            old_short: short_sha(old_oid),
+            new_short: short_sha(new_oid),
+        };
+    }
+
+    let commits = (|| -> Result<Vec<ChangelogEntry>> {
+        let mut walk = repo.revwalk()?;
+        walk.push(new_oid)?;
+        walk.hide(old_oid)?;
+
+        let mut entries: Vec<ChangelogEntry> = walk
+            .filter_map(|oid| oid.ok())
+            .filter_map(|oid| repo.find_commit(oid).ok())
+            .map(|commit| ChangelogEntry {
+                short_sha: short_sha(commit.id()),
+                summary: commit.summary().unwrap_or("").to_string(),
+            })
+            .collect();
+        entries.reverse(); // revwalk yields newest-first; we want oldest-first
+        Ok(entries)
+    })()
+    .unwrap_or_default();
+
+    RepoUpdate::Commits {
+        old_short: short_sha(old_oid),
+        new_short: short_sha(new_oid),
+        commits,
+    }
+}
+
+/// Format the first 7 characters of an object id, matching `git`'s default
+/// abbreviated SHA length.
+fn short_sha(oid: git2::Oid) -> String {
+    oid.to_string().chars().take(7).collect()
+}
+
+/// Checkout `reference`, unshallowing the repository and retrying once if
+/// it can't be resolved in a depth-limited clone.
+fn checkout_reference_unshallowing(
+    repo: &Repository,
+    reference: &GitReference,
+    depth: Option<u32>,
+) -> Result<()> {
+    match checkout_reference(repo, reference) {
+        Ok(()) => Ok(()),
+        Err(e) if depth.is_some() => {
+            fetch_origin(repo, None)
+                .with_context(|| format!("unshallow fetch for {reference:?}"))?;
+            checkout_reference(repo, reference).with_context(|| {
+                format!("{reference:?} not found even after unshallowing (original error: {e})")
+            })
+        }
+        Err(e) => Err(e),
     }
 }
 
 /// Perform `git fetch origin` to update remote refs.
 ///
 /// This fetches both branches and tags from `origin` into the local repository.
+/// `depth` limits the fetch to the last `n` commits per ref; `None` fetches
+/// full history.
 ///
 /// # Errors
 /// Returns an error if the fetch operation fails.
-pub fn fetch_origin(repo: &Repository) -> Result<()> {
-    let mut fo = fetch_opts_with_creds();
+pub fn fetch_origin(repo: &Repository, depth: Option<u32>) -> Result<()> {
+    let mut fo = fetch_opts_with_creds(depth);
 
     let mut remote = repo.find_remote("origin")?;
     remote
@@ -157,6 +430,184 @@ pub fn fetch_origin(repo: &Repository) -> Result<()> {
     Ok(())
 }
 
+/// Resolve the commit a [`GitReference`] currently points to on the remote,
+/// without touching the working tree. Mirrors the precedence used by
+/// [`checkout_reference`] for each variant, except that `Rev` skips the
+/// local-branch lookup and starts at `refs/remotes/origin/<rev>`.
+fn resolve_target_commit<'repo>(
+    repo: &'repo Repository,
+    reference: &GitReference,
+) -> Option<git2::Commit<'repo>> {
+    match reference {
+        GitReference::Default => resolve_default_branch_ref(repo)
+            .ok()
+            .and_then(|r| repo.find_reference(&r).ok())
+            .and_then(|r| r.peel_to_commit().ok()),
+        GitReference::Branch(name) => repo
+            .find_reference(&format!("refs/remotes/origin/{name}"))
+            .ok()
+            .and_then(|r| r.peel_to_commit().ok()),
+        GitReference::Tag(name) => repo
+            .revparse_single(&format!("refs/tags/{name}"))
+            .ok()
+            .and_then(|o| o.peel(ObjectType::Commit).ok())
+            .and_then(|o| o.into_commit().ok()),
+        GitReference::Rev(rev) => repo
+            .find_reference(&format!("refs/remotes/origin/{rev}"))
+            .ok()
+            .and_then(|r| r.peel_to_commit().ok())
+            .or_else(|| {
+                repo.revparse_single(&format!("refs/tags/{rev}"))
+                    .ok()
+                    .and_then(|o| o.peel(ObjectType::Commit).ok())
+                    .and_then(|o| o.into_commit().ok())
+            })
+            .or_else(|| {
+                repo.revparse_single(rev)
+                    .ok()
+                    .and_then(|o| o.peel(ObjectType::Commit).ok())
+                    .and_then(|o| o.into_commit().ok())
+            }),
+    }
+}
+
+/// Report whether a plugin repository is behind its remote target, without
+/// mutating the working tree.
+///
+/// Unlike [`ensure_repo`], this only runs `fetch_origin` to refresh remote
+/// refs (no checkout/reset): it resolves the current `HEAD` commit and the
+/// target tip ([`resolve_target_commit`]), then compares them with
+/// `Repository::graph_ahead_behind`.
+///
+/// # Errors
+/// Returns an error if the repository cannot be opened or the fetch fails.
+/// A target revision that cannot be resolved is reported via
+/// `UpdateStatus.unknown` rather than as an error.
+pub fn update_status(dest: &Path, reference: &GitReference) -> Result<UpdateStatus> {
+    let repo = Repository::open(dest)?;
+    fetch_origin(&repo, None)?;
+
+    let head = repo.head()?.peel_to_commit()?;
+    let detached = repo.head_detached()?;
+
+    let dirty = {
+        let mut opts = StatusOptions::new();
+        opts.include_untracked(false).include_ignored(false);
+        !repo.statuses(Some(&mut opts))?.is_empty()
+    };
+
+    let target = resolve_target_commit(&repo, reference);
+
+    let Some(target) = target else {
+        return Ok(UpdateStatus {
+            dirty,
+            detached,
+            unknown: true,
+            ..Default::default()
+        });
+    };
+
+    let (ahead, behind) = repo.graph_ahead_behind(head.id(), target.id())?;
+    Ok(UpdateStatus {
+        ahead,
+        behind,
+        dirty,
+        detached,
+        unknown: false,
+    })
+}
+
+/// Resolve the full `HEAD` commit SHA for an already-synced repository, for
+/// recording in the lockfile (see [`crate::lock`]).
+///
+/// # Errors
+/// Returns an error if `dest` isn't a valid git repository or has no commits.
+pub fn head_commit_sha(dest: &Path) -> Result<String> {
+    let repo = Repository::open(dest)
+        .with_context(|| format!("failed to open repo at {}", dest.display()))?;
+    Ok(repo.head()?.peel_to_commit()?.id().to_string())
+}
+
+/// Open a local repository for read-only inspection (see [`local_status`]).
+///
+/// A thin wrapper so callers outside this module (e.g. `rz list`) don't need
+/// to depend on the `git2` crate directly to hold onto a repository handle.
+///
+/// # Errors
+/// Returns an error if `dest` isn't a valid git repository.
+pub fn open_repo(dest: &Path) -> Result<Repository> {
+    Repository::open(dest).with_context(|| format!("failed to open repo at {}", dest.display()))
+}
+
+/// Where a repository's `HEAD` currently points, for display (see [`local_status`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalHead {
+    /// Attached to a branch, with its (short) name.
+    Branch(String),
+    /// Detached, with the current commit's short SHA.
+    Detached(String),
+}
+
+/// Local-only snapshot of a repository's checkout state, for display.
+///
+/// Unlike [`update_status`], computing this never touches the network (no
+/// `fetch_origin` call), so it's cheap enough to compute per plugin every
+/// time a listing command runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalStatus {
+    pub head: LocalHead,
+    /// Whether the working tree has uncommitted changes.
+    pub dirty: bool,
+}
+
+/// Compute [`LocalStatus`] for an already-open repository handle.
+///
+/// # Errors
+/// Returns an error if `HEAD` can't be resolved or the status scan fails.
+pub fn local_status(repo: &Repository) -> Result<LocalStatus> {
+    let head_ref = repo.head()?;
+    let head = if repo.head_detached()? {
+        let oid = head_ref.peel_to_commit()?.id();
+        LocalHead::Detached(oid.to_string().chars().take(7).collect())
+    } else {
+        let name = head_ref.shorthand().unwrap_or("HEAD").to_string();
+        LocalHead::Branch(name)
+    };
+
+    let dirty = {
+        let mut opts = StatusOptions::new();
+        opts.include_untracked(false).include_ignored(false);
+        !repo.statuses(Some(&mut opts))?.is_empty()
+    };
+
+    Ok(LocalStatus { head, dirty })
+}
+
+/// Describe an already-open repository's `HEAD` relative to its nearest
+/// reachable tag, e.g. `v1.4.2-3-g0a1b2c3` (tag, commits-since, abbreviated
+/// SHA) or `v1.4.2-3-g0a1b2c3*` if the working tree is dirty.
+///
+/// Returns `None` rather than an error when no tag is reachable from `HEAD`
+/// (a perfectly normal state for an untagged repo or shallow clone) — in
+/// that case callers should fall back to a plain short SHA. Any other
+/// failure to describe `HEAD`, including an unresolvable `HEAD`, also
+/// yields `None`.
+///
+/// # Errors
+/// Returns an error only if formatting the description fails.
+pub fn describe_repo(repo: &Repository) -> Result<Option<String>> {
+    let mut describe_opts = DescribeOptions::new();
+    describe_opts.describe_tags();
+
+    let Ok(description) = repo.describe(&describe_opts) else {
+        return Ok(None);
+    };
+
+    let mut fmt_opts = DescribeFormatOptions::new();
+    fmt_opts.abbreviated_size(7).dirty_suffix("*");
+    Ok(Some(description.format(Some(&fmt_opts))?))
+}
+
 /// Attach HEAD to the given branch reference and update the working tree.
 ///
 /// Moves HEAD to the provided branch ref (attached state) and checks out
@@ -174,7 +625,78 @@ fn checkout_attach_to_reference(repo: &Repository, reference: &Reference) -> Res
     Ok(())
 }
 
-/// Checkout a specific revision (branch, tag, or commit).
+/// Checkout a [`GitReference`], dispatching to the deterministic resolver
+/// for `Branch`/`Tag`, the loose fallback for `Rev`, or the default-branch
+/// attach for `Default`.
+///
+/// # Errors
+/// Returns an error if the reference cannot be resolved or if checkout fails.
+pub fn checkout_reference(repo: &Repository, reference: &GitReference) -> Result<()> {
+    match reference {
+        GitReference::Branch(name) => checkout_branch(repo, name),
+        GitReference::Tag(name) => checkout_tag(repo, name),
+        GitReference::Rev(rev) => checkout_rev(repo, rev),
+        GitReference::Default => attach_default_branch(repo),
+    }
+}
+
+/// Checkout a branch by name, attaching HEAD to it and hard-resetting it to
+/// `origin/<name>` (creating a local tracking branch first if one doesn't
+/// exist yet). With no `origin/<name>`, an existing local branch is attached
+/// as-is.
+///
+/// # Errors
+/// Returns an error if neither a local nor a remote branch named `name` exists.
+fn checkout_branch(repo: &Repository, name: &str) -> Result<()> {
+    let Ok(remote_ref) = repo.find_reference(&format!("refs/remotes/origin/{name}")) else {
+        let b = repo
+            .find_branch(name, BranchType::Local)
+            .with_context(|| format!("branch not found: {name}"))?;
+        return checkout_attach_to_reference(repo, &b.into_reference());
+    };
+    let target_commit = remote_ref.peel_to_commit()?;
+
+    let reference = match repo.find_branch(name, BranchType::Local) {
+        Ok(b) => b.into_reference(),
+        Err(_) => {
+            let mut b = repo.branch(name, &target_commit, true)?;
+            b.set_upstream(Some(&format!("origin/{name}")))?;
+            b.into_reference()
+        }
+    };
+
+    // Attach first, so the hard reset moves this branch rather than
+    // whatever HEAD pointed at before.
+    repo.set_head(
+        reference
+            .name()
+            .ok_or_else(|| anyhow!("invalid reference name"))?,
+    )?;
+    repo.reset(target_commit.as_object(), ResetType::Hard, None)?;
+    repo.checkout_head(Some(CheckoutBuilder::new().force()))?;
+    Ok(())
+}
+
+/// Checkout a tag by name, peeling a possibly-annotated tag object down to
+/// its commit and detaching HEAD there.
+///
+/// # Errors
+/// Returns an error if no tag named `name` exists.
+fn checkout_tag(repo: &Repository, name: &str) -> Result<()> {
+    let obj = repo
+        .revparse_single(&format!("refs/tags/{name}"))
+        .with_context(|| format!("tag not found: {name}"))?;
+    let commit = obj
+        .peel(ObjectType::Commit)?
+        .into_commit()
+        .map_err(|_| anyhow!("tag {name} didn't peel to a commit"))?;
+    repo.checkout_tree(commit.as_object(), None)?;
+    repo.set_head_detached(commit.id())?;
+    Ok(())
+}
+
+/// Checkout a specific revision (branch, tag, or commit) from a loose,
+/// untyped string — the back-compat fallback for [`GitReference::Rev`].
 ///
 /// Resolution order:
 /// 1. Local branch (`refs/heads/<rev>`) → attach HEAD to the branch
@@ -187,7 +709,7 @@ fn checkout_attach_to_reference(repo: &Repository, reference: &Reference) -> Res
 ///
 /// # Errors
 /// Returns an error if the revision cannot be resolved or if checkout fails.
-pub fn checkout_rev(repo: &Repository, rev: &str) -> Result<()> {
+fn checkout_rev(repo: &Repository, rev: &str) -> Result<()> {
     if let Ok(reference) = repo.find_reference(&format!("refs/heads/{}", rev)) {
         checkout_attach_to_reference(repo, &reference)?;
         return Ok(());
@@ -231,3 +753,58 @@ pub fn checkout_rev(repo: &Repository, rev: &str) -> Result<()> {
     repo.set_head_detached(commit.id())?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_from_url_handles_https_and_ssh() {
+        assert_eq!(
+            host_from_url("https://gitlab.com/owner/repo.git"),
+            Some("gitlab.com")
+        );
+        assert_eq!(
+            host_from_url("git@codeberg.org:owner/repo.git"),
+            Some("codeberg.org")
+        );
+        assert_eq!(host_from_url("not-a-url"), None);
+    }
+
+    #[test]
+    fn token_env_var_for_host_matches_known_forges() {
+        assert_eq!(token_env_var_for_host("github.com"), Some("GITHUB_TOKEN"));
+        assert_eq!(token_env_var_for_host("gitlab.com"), Some("GITLAB_TOKEN"));
+        assert_eq!(token_env_var_for_host("codeberg.org"), Some("GITEA_TOKEN"));
+        assert_eq!(
+            token_env_var_for_host("git.mygitea.example"),
+            Some("GITEA_TOKEN")
+        );
+        assert_eq!(
+            token_env_var_for_host("bitbucket.org"),
+            Some("BITBUCKET_TOKEN")
+        );
+        assert_eq!(token_env_var_for_host("example.com"), None);
+    }
+
+    #[test]
+    fn ensure_clean_repo_state_accepts_a_fresh_repo() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+        assert!(ensure_clean_repo_state(&repo).is_ok());
+    }
+
+    #[test]
+    fn ensure_clean_repo_state_rejects_an_in_progress_merge() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = Repository::init(tmp.path()).unwrap();
+
+        // Fabricate a MERGE_HEAD to simulate a stuck merge; repo.state()
+        // reads this file directly, so no real merge commits are needed.
+        std::fs::write(repo.path().join("MERGE_HEAD"), "0".repeat(40)).unwrap();
+
+        let err = ensure_clean_repo_state(&repo).unwrap_err();
+        let msg = format!("{err:#}");
+        assert!(msg.contains("in-progress"), "unexpected error: {msg}");
+    }
+}
@@ -1,7 +1,11 @@
 //! Git integration layer.
 //!
-//! This module wraps the actual backend implementation (`git2_backend`)
-//! and re-exports only the stable public API (`ensure_repo`).
+//! This module wraps the actual backend implementation (`git2_backend`) and
+//! re-exports its stable public API: repo sync (`ensure_repo`), remote-status
+//! comparison (`update_status`), local-only status (`local_status`,
+//! `open_repo`), tag description (`describe_repo`), commit SHA lookup
+//! (`head_commit_sha`), and the types that go with them (`GitReference`,
+//! `RepoUpdate`, `UpdateStatus`, `LocalHead`, `LocalStatus`).
 //!
 //! The idea is to hide internal implementation details (currently based on `git2` crate)
 //! so that future backends or alternative implementations could be swapped in
@@ -11,6 +15,32 @@ mod git2_backend;
 
 /// Ensure that a git repository exists locally and is up-to-date.
 ///
-/// This is the only public API exported from the `git` module.
 /// Other modules should use this instead of depending directly on `git2_backend`.
 pub use git2_backend::ensure_repo;
+
+/// What changed (if anything) as a result of [`ensure_repo`]. See
+/// [`RepoUpdate`] (its `Commits` variant carries a `Vec` of per-commit
+/// changelog entries).
+pub use git2_backend::RepoUpdate;
+
+/// Typed checkout intent for a plugin (branch, tag, loose rev, or default branch).
+pub use git2_backend::GitReference;
+
+/// Compare a plugin repository's checked-out revision against its remote
+/// target without mutating the working tree. See [`UpdateStatus`].
+pub use git2_backend::{UpdateStatus, update_status};
+
+/// An open repository handle, for callers (e.g. `rz list`) that want to hold
+/// onto it across multiple lookups rather than reopening on every call.
+pub use git2::Repository;
+
+/// Open a repository and compute a local-only (no network) snapshot of its
+/// checkout state. See [`open_repo`] and [`LocalStatus`].
+pub use git2_backend::{LocalHead, LocalStatus, local_status, open_repo};
+
+/// Describe a repository's `HEAD` relative to its nearest reachable tag, for
+/// display (e.g. `rz list`). See [`describe_repo`].
+pub use git2_backend::describe_repo;
+
+/// Resolve a synced repository's full `HEAD` commit SHA, for the lockfile.
+pub use git2_backend::head_commit_sha;
@@ -1,9 +1,11 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::io::{self, Write};
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
-use crate::config::load_config;
-use crate::paths::paths;
+use crate::compile::compiled_init_path;
+use crate::config::{Config, PluginFilterConfig, load_config};
+use crate::order::resolve_order;
+use crate::paths::{Paths, paths, rz_home};
 
 /// Escape a path for safe inclusion in a Zsh double-quoted string.
 fn zsh_quote_path(p: &str) -> String {
@@ -11,11 +13,16 @@ fn zsh_quote_path(p: &str) -> String {
     p.replace('\\', "\\\\").replace('"', "\\\"")
 }
 
-pub fn cmd_init() -> Result<()> {
-    let cfg = load_config()?;
-    let p = paths()?; // has .repos, .plugins, .bin, etc.
-
-    // Collect absolute fpath dirs from config-driven plugins (type = "fpath")
+/// Collect absolute fpath directories for every `type = "fpath"` plugin,
+/// resolved against that plugin's content directory (see
+/// [`crate::config::Plugin::content_dir`]). Sorted and deduped for stable
+/// output. Shared by [`cmd_init`] and [`crate::compile::cmd_compile`].
+///
+/// Plugins whose repo hasn't been synced yet (missing repo dir) are skipped.
+/// Explicit `fpath_dirs` entries are always honored; plugins with
+/// `autodetect = true` additionally get every directory found by
+/// [`fpath_dirs_autodetect`] merged in.
+pub(crate) fn resolve_fpath_dirs(cfg: &Config, p: &Paths) -> Vec<String> {
     let mut fpath_dirs: Vec<String> = Vec::new();
 
     for pl in &cfg.plugins {
@@ -23,9 +30,7 @@ pub fn cmd_init() -> Result<()> {
         if ty != "fpath" {
             continue;
         }
-        // slug = owner/repo -> owner__repo
-        let slug = pl.repo.replace('/', "__");
-        let root = p.repos.join(&slug);
+        let root = pl.content_dir(p);
 
         // If plugin root is missing, skip (user may not have synced yet)
         if !root.is_dir() {
@@ -33,7 +38,7 @@ pub fn cmd_init() -> Result<()> {
         }
 
         for d in &pl.fpath_dirs {
-            let cand: PathBuf = {
+            let cand: std::path::PathBuf = {
                 let pd = Path::new(d);
                 if pd.is_absolute() {
                     pd.to_path_buf()
@@ -48,11 +53,88 @@ pub fn cmd_init() -> Result<()> {
                 fpath_dirs.push(s);
             }
         }
+
+        if pl.autodetect {
+            fpath_dirs.extend(fpath_dirs_autodetect(&root, &cfg.plugin_filter));
+        }
     }
 
     // Sort + dedup to stabilize output
     fpath_dirs.sort();
     fpath_dirs.dedup();
+    fpath_dirs
+}
+
+/// Scan `root` for zsh completion files (names starting with `_`, not `_`
+/// itself, regular files or symlinks) and return the absolute, canonicalized
+/// path of every directory containing at least one, sorted and deduped.
+///
+/// This is the opt-in counterpart to [`resolve_fpath_dirs`]'s explicit
+/// `fpath_dirs` list, gated behind a plugin's `autodetect = true` — useful
+/// for completion-only repos that would otherwise need every completion
+/// subdirectory hand-listed.
+///
+/// `filter` is applied to each candidate completion file name (see
+/// [`PluginFilterConfig::is_extension_allowed`]), so e.g. an
+/// `excluded_extensions = ["bak"]` rule keeps a stray `_mytool.bak` from
+/// counting as a completion file.
+fn fpath_dirs_autodetect(root: &Path, filter: &PluginFilterConfig) -> Vec<String> {
+    let mut dirs = Vec::new();
+    walk_for_completions(root, filter, &mut dirs);
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+/// Recursive helper for [`fpath_dirs_autodetect`]: visits `dir`, records it
+/// in `found` if it directly contains a completion file accepted by
+/// `filter`, then recurses into its subdirectories (skipping `.git`).
+fn walk_for_completions(dir: &Path, filter: &PluginFilterConfig, found: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    let mut has_completion = false;
+    let mut subdirs = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let file_name = entry.file_name();
+        let name = file_name.to_string_lossy();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if name != ".git" {
+                subdirs.push(entry.path());
+            }
+        } else if name.starts_with('_') && name != "_" && filter.is_extension_allowed(&name) {
+            has_completion = true;
+        }
+    }
+
+    if has_completion {
+        let canon = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        found.push(canon.to_string_lossy().to_string());
+    }
+    for sub in subdirs {
+        walk_for_completions(&sub, filter, found);
+    }
+}
+
+pub fn cmd_init() -> Result<()> {
+    let cfg = load_config()?;
+    let p = paths()?; // has .repos, .plugins, .bin, .cache, .config
+
+    // Resolve the base directories once here (see `crate::paths`) and emit
+    // them as literal paths, instead of having the shell recompute
+    // `${XDG_CONFIG_HOME:-$HOME}/.rz`-style logic at every shell startup.
+    let base_dirs_snippet = format!(
+        "  typeset -g RZ_HOME=\"{}\"\n  typeset -g RZ_BIN=\"{}\"\n  typeset -g RZ_PLUGINS=\"{}\"\n",
+        zsh_quote_path(&rz_home()?.to_string_lossy()),
+        zsh_quote_path(&p.bin.to_string_lossy()),
+        zsh_quote_path(&p.plugins.to_string_lossy()),
+    );
+
+    let fpath_dirs = resolve_fpath_dirs(&cfg, &p);
 
     // Build Zsh snippet that prepends fpath entries (if any)
     let fpath_snippet = if fpath_dirs.is_empty() {
@@ -69,8 +151,47 @@ pub fn cmd_init() -> Result<()> {
         )
     };
 
-    // Render final init.zsh (template with {FPATh_SNIPPET} placeholder)
-    let script = INIT_ZSH_TEMPLATE.replace("{FPATh_SNIPPET}", &fpath_snippet);
+    // Prefer a compiled init script (see `rz compile`) when one exists: a
+    // single `source` call instead of one per plugin. Falls back to the
+    // per-plugin guarded `source` lines otherwise.
+    let compiled = compiled_init_path(&p);
+    let source_snippet = if compiled.is_file() {
+        let compiled_quoted = zsh_quote_path(&compiled.to_string_lossy());
+        format!(
+            "  # using compiled plugin script from `rz compile`\n  if [[ -f \"{compiled_quoted}\" ]]; then source \"{compiled_quoted}\"; fi\n"
+        )
+    } else {
+        // Resolve the effective source order (see `crate::order`) and emit one
+        // guarded `source` line per `source`-type plugin, in that order. This
+        // replaces the old runtime slug-classification loop with a static list
+        // computed once, here, at `rz init` time.
+        let order = resolve_order(&cfg.plugins).context("failed to resolve plugin order")?;
+        let mut source_snippet = String::new();
+        for idx in order {
+            let pl = &cfg.plugins[idx];
+            let ty = pl.r#type.as_deref().unwrap_or("source");
+            if ty == "fpath" {
+                continue;
+            }
+            let slug = pl.slug();
+            let plug_name = pl.name.as_deref().unwrap_or(&slug);
+            let link = p.plugins.join(plug_name);
+            let link_quoted = zsh_quote_path(&link.to_string_lossy());
+            source_snippet.push_str(&format!(
+                "  if [[ -L \"{link_quoted}\" && -f \"{link_quoted}\" ]]; then source \"{link_quoted}\"; fi\n"
+            ));
+        }
+        if source_snippet.is_empty() {
+            source_snippet.push_str("  # no plugins configured\n");
+        }
+        source_snippet
+    };
+
+    // Render final init.zsh (fill in the template placeholders above)
+    let script = INIT_ZSH_TEMPLATE
+        .replace("{BASE_DIRS_SNIPPET}", &base_dirs_snippet)
+        .replace("{FPATh_SNIPPET}", &fpath_snippet)
+        .replace("{SOURCE_SNIPPET}", &source_snippet);
 
     io::stdout().write_all(script.as_bytes())?;
     Ok(())
@@ -78,15 +199,14 @@ pub fn cmd_init() -> Result<()> {
 
 /// Static init.zsh template.
 /// NOTE:
-/// - {FPATh_SNIPPET} will be replaced at runtime with computed fpath lines.
+/// - {BASE_DIRS_SNIPPET} is replaced with the resolved RZ_HOME/RZ_BIN/RZ_PLUGINS.
+/// - {FPATh_SNIPPET} is replaced with computed fpath lines.
+/// - {SOURCE_SNIPPET} is replaced with the resolved, ordered `source` lines.
 /// - Avoid `local` in this script (it is eval'ed into user's interactive shell).
 const INIT_ZSH_TEMPLATE: &str = r#"# rat-zsh init
 if [[ -z "${_RZ_INIT:-}" ]]; then
   typeset -g _RZ_INIT=1
-  typeset -g RZ_HOME="${XDG_CONFIG_HOME:-$HOME}/.rz"
-  typeset -g RZ_BIN="$RZ_HOME/bin"
-  typeset -g RZ_PLUGINS="$RZ_HOME/plugins"
-
+{BASE_DIRS_SNIPPET}
   # Prepend rz bin to PATH
   export PATH="$RZ_BIN:$PATH"
 
@@ -98,53 +218,66 @@ if [[ -z "${_RZ_INIT:-}" ]]; then
     compinit -u
   fi
 
-  # Source-order management (tail plugins last)
-  typeset -a _rz_tail_slugs=(
-    zsh-users__zsh-autosuggestions
-    zsh-users__zsh-syntax-highlighting
-  )
-
-  typeset -a _rz_normal _rz_tail
-  _rz_normal=()
-  _rz_tail=()
-
-  # Classify plugin entries under $RZ_PLUGINS
-  typeset p target slug
-  for p in "$RZ_PLUGINS"/*(N@-); do
-    target="${p:A}"
-    slug=""
-    if [[ $target == */repos/* ]]; then
-      slug="${${target##*/repos/}%%/*}"
-    fi
-    typeset -i is_tail=0
-    for s in $_rz_tail_slugs; do
-      if [[ $slug == $s ]]; then is_tail=1; break; fi
-    done
-    if (( is_tail )); then _rz_tail+=("$p"); else _rz_normal+=("$p"); fi
-  done
-
-  # Source normal plugins
-  for p in $_rz_normal; do
-    if [[ -L "$p" && -f "$p" ]]; then source "$p"; continue; fi
-    case "$p" in
-      *.zsh|*.plugin.zsh|*.zsh-theme) source "$p" ;;
-    esac
-  done
-
-  # Source tail plugins in fixed order
-  typeset q
-  for s in $_rz_tail_slugs; do
-    for q in $_rz_tail; do
-      target="${q:A}"
-      slug=""
-      [[ $target == */repos/* ]] && slug="${${target##*/repos/}%%/*}"
-      if [[ $slug == $s ]]; then
-        if [[ -L "$q" && -f "$q" ]]; then source "$q"; continue; fi
-        case "$q" in
-          *.zsh|*.plugin.zsh|*.zsh-theme) source "$q" ;;
-        esac
-      fi
-    done
-  done
+  # Source plugins in the effective order (see `rz order`), precomputed
+  # from config.toml's after/before edges at `rz init` time
+{SOURCE_SNIPPET}
 fi
 "#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn fpath_dirs_autodetect_finds_dirs_with_completion_files() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+
+        let comp_dir = root.join("src");
+        std::fs::create_dir_all(&comp_dir).unwrap();
+        std::fs::write(comp_dir.join("_mytool"), "#compdef mytool").unwrap();
+        std::fs::write(comp_dir.join("mytool.plugin.zsh"), "# plugin").unwrap();
+
+        let plain_dir = root.join("docs");
+        std::fs::create_dir_all(&plain_dir).unwrap();
+        std::fs::write(plain_dir.join("README.md"), "# readme").unwrap();
+
+        let got = fpath_dirs_autodetect(root, &PluginFilterConfig::default());
+        assert_eq!(got.len(), 1);
+        assert_eq!(
+            std::path::PathBuf::from(&got[0]),
+            std::fs::canonicalize(&comp_dir).unwrap()
+        );
+    }
+
+    #[test]
+    fn fpath_dirs_autodetect_ignores_bare_underscore_and_git_dir() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+
+        std::fs::write(root.join("_"), "not a completion").unwrap();
+
+        let git_dir = root.join(".git");
+        std::fs::create_dir_all(&git_dir).unwrap();
+        std::fs::write(git_dir.join("_looks_like_one"), "").unwrap();
+
+        let got = fpath_dirs_autodetect(root, &PluginFilterConfig::default());
+        assert!(got.is_empty());
+    }
+
+    #[test]
+    fn fpath_dirs_autodetect_ignores_completion_files_excluded_by_filter() {
+        let tmp = tempdir().unwrap();
+        let root = tmp.path();
+
+        std::fs::write(root.join("_mytool.bak"), "#compdef mytool").unwrap();
+
+        let filter = PluginFilterConfig {
+            allowed_extensions: Vec::new(),
+            excluded_extensions: vec!["bak".to_string()],
+        };
+        let got = fpath_dirs_autodetect(root, &filter);
+        assert!(got.is_empty());
+    }
+}
@@ -6,18 +6,27 @@
 //!
 //! This file is primarily intended for developers hacking on `rz`.
 
+mod compile;
 mod config;
 mod git;
 mod init;
+mod list;
+mod lock;
 mod order;
 mod paths;
+mod status;
 mod sync;
+mod template;
 mod upgrade;
 
 /// Re-export commonly used types and commands so they can be accessed from `rz::*`.
-pub use config::{Config, Plugin, cmd_list};
+pub use compile::cmd_compile;
+pub use config::{Config, Plugin, load_config};
 pub use init::cmd_init;
+pub use list::{ListFormat, cmd_list};
+pub use lock::cmd_lock;
 pub use order::cmd_order;
 pub use paths::rz_home;
+pub use status::cmd_status;
 pub use sync::cmd_sync;
-pub use upgrade::cmd_upgrade;
+pub use upgrade::{cmd_upgrade, cmd_upgrade_rollback};
@@ -1,5 +1,48 @@
-use crate::config::load_config;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::path::{Path, PathBuf};
+
 use anyhow::Result;
+use serde::Serialize;
+
+use crate::config::load_config;
+use crate::git::{LocalHead, Repository, describe_repo, local_status, open_repo};
+use crate::paths::paths;
+
+/// Output mode for [`cmd_list`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListFormat {
+    /// The default human-readable listing (see [`cmd_list`]'s doc comment
+    /// for an example).
+    #[default]
+    Text,
+    /// A JSON array on stdout, one object per plugin, for piping into `jq`
+    /// or another tool — see [`ListEntry`].
+    Json,
+}
+
+/// A single plugin's listing entry, in [`ListFormat::Json`] mode. Mirrors
+/// exactly what the default text format renders per plugin.
+#[derive(Debug, Serialize)]
+struct ListEntry {
+    slug: String,
+    display: String,
+    source: String,
+    role: String,
+    branch: Option<String>,
+    /// Short commit SHA, if the plugin's repo has been synced and is
+    /// currently in a detached-HEAD state.
+    commit: Option<String>,
+    /// The pinned `rev` from `config.toml`, if set.
+    pinned_rev: Option<String>,
+    /// Whether the working tree has uncommitted changes. `false` if the
+    /// repo hasn't been synced yet.
+    dirty: bool,
+    /// Nearest-tag description (e.g. `v1.4.2-3-g0a1b2c3`), if `HEAD` can
+    /// reach a tag. `None` if the repo hasn't been synced yet or has no
+    /// reachable tags — see [`fmt_describe`].
+    describe: Option<String>,
+}
 
 /// CLI command: print a human-readable list of plugins.
 ///
@@ -7,21 +50,139 @@ use anyhow::Result;
 /// - name or repo (for identification)
 /// - source (e.g., `github`)
 /// - type (`source`, `fpath`, etc.)
+/// - git status, if its repo has been synced: the branch (or short commit,
+///   if detached), the pinned `rev` from config (if any), and a trailing `*`
+///   if the working tree has uncommitted changes
+/// - a nearest-tag description (e.g. `v1.4.2-3-g0a1b2c3`, see
+///   [`crate::git::describe_repo`] and [`fmt_describe`]), falling back
+///   silently to nothing when no tag is reachable from `HEAD`
+///
+/// Repositories are opened at most once per command invocation (cached by
+/// repo directory, see [`git_status_suffix`] and [`describe_for`]).
+///
+/// With `format = `[`ListFormat::Json`], the same information is instead
+/// printed as a JSON array of [`ListEntry`] objects, one per plugin, for
+/// scripting (`rz list --format json | jq`).
 ///
 /// Example output:
 /// ```text
-/// - zsh-autosuggestions (github) [source]
-/// - zsh-completions (github) [fpath]
+/// - zsh-autosuggestions (github) [source] @main (v2.1.0-3-gabcdef1)
+/// - zsh-completions (github) [fpath] @a1b2c3d (pinned: v1.0.0) *
 /// ```
 ///
 /// # Errors
 /// - Returns an error if `config.toml` cannot be loaded or parsed.
-pub fn cmd_list() -> Result<()> {
+/// - In JSON mode, returns an error if serialization fails.
+pub fn cmd_list(format: ListFormat) -> Result<()> {
     let cfg = load_config()?;
-    for pl in cfg.plugins {
+    let p = paths()?;
+    let mut repo_cache: HashMap<PathBuf, Repository> = HashMap::new();
+
+    if format == ListFormat::Json {
+        let entries: Vec<ListEntry> = cfg
+            .plugins
+            .iter()
+            .map(|pl| {
+                let role = pl.r#type.as_deref().unwrap_or("source").to_string();
+                let status = local_status_for(&mut repo_cache, &pl.content_dir(&p));
+                ListEntry {
+                    slug: pl.slug(),
+                    display: pl.display_name(),
+                    source: pl.source.clone(),
+                    role,
+                    branch: status.as_ref().and_then(|s| match &s.head {
+                        LocalHead::Branch(name) => Some(name.clone()),
+                        LocalHead::Detached(_) => None,
+                    }),
+                    commit: status.as_ref().and_then(|s| match &s.head {
+                        LocalHead::Detached(sha) => Some(sha.clone()),
+                        LocalHead::Branch(_) => None,
+                    }),
+                    pinned_rev: pl.rev.clone(),
+                    dirty: status.as_ref().map(|s| s.dirty).unwrap_or(false),
+                    describe: describe_for(&mut repo_cache, &pl.content_dir(&p)),
+                }
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    for pl in &cfg.plugins {
         let t = pl.r#type.as_deref().unwrap_or("source");
-        let display = pl.name.as_deref().unwrap_or(&pl.repo);
-        println!("- {} ({}) [{}]", display, pl.source, t);
+        let display = pl.display_name();
+        let content_dir = pl.content_dir(&p);
+        let status = git_status_suffix(&mut repo_cache, &content_dir, pl.rev.as_deref());
+        let describe = fmt_describe(describe_for(&mut repo_cache, &content_dir));
+        println!("- {} ({}) [{}]{}{}", display, pl.source, t, status, describe);
     }
     Ok(())
 }
+
+/// Open (or reuse a cached handle to) the repository at `repo_dir` and
+/// describe its `HEAD` relative to the nearest reachable tag, or `None` if
+/// it hasn't been synced yet, can't be read, or has no reachable tags.
+fn describe_for(cache: &mut HashMap<PathBuf, Repository>, repo_dir: &Path) -> Option<String> {
+    if !repo_dir.is_dir() {
+        return None;
+    }
+    let repo = match cache.entry(repo_dir.to_path_buf()) {
+        Entry::Occupied(e) => e.into_mut(),
+        Entry::Vacant(e) => match open_repo(repo_dir) {
+            Ok(repo) => e.insert(repo),
+            Err(_) => return None,
+        },
+    };
+    describe_repo(repo).ok().flatten()
+}
+
+/// Render a [`describe_for`] result as a trailing `" (<description>)"`
+/// string, or an empty string when no description is available (the plugin
+/// hasn't been synced yet, or `HEAD` can't reach any tag).
+fn fmt_describe(describe: Option<String>) -> String {
+    describe.map(|d| format!(" ({d})")).unwrap_or_default()
+}
+
+/// Open (or reuse a cached handle to) the repository at `repo_dir` and
+/// compute its [`crate::git::LocalStatus`], or `None` if it hasn't been
+/// synced yet or can't be read.
+fn local_status_for(
+    cache: &mut HashMap<PathBuf, Repository>,
+    repo_dir: &Path,
+) -> Option<crate::git::LocalStatus> {
+    if !repo_dir.is_dir() {
+        return None;
+    }
+    let repo = match cache.entry(repo_dir.to_path_buf()) {
+        Entry::Occupied(e) => e.into_mut(),
+        Entry::Vacant(e) => match open_repo(repo_dir) {
+            Ok(repo) => e.insert(repo),
+            Err(_) => return None,
+        },
+    };
+    local_status(repo).ok()
+}
+
+/// Render a plugin's git status as a trailing `" @<branch-or-sha> (pinned:
+/// <rev>) *"` string (each part omitted when not applicable), or an empty
+/// string if `repo_dir` hasn't been synced yet or can't be read.
+fn git_status_suffix(
+    cache: &mut HashMap<PathBuf, Repository>,
+    repo_dir: &Path,
+    pinned_rev: Option<&str>,
+) -> String {
+    let Some(status) = local_status_for(cache, repo_dir) else {
+        return String::new();
+    };
+
+    let head = match &status.head {
+        LocalHead::Branch(name) => format!("@{name}"),
+        LocalHead::Detached(short_sha) => format!("@{short_sha}"),
+    };
+    let pin = pinned_rev
+        .map(|r| format!(" (pinned: {r})"))
+        .unwrap_or_default();
+    let dirty = if status.dirty { " *" } else { "" };
+
+    format!(" {head}{pin}{dirty}")
+}
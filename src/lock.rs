@@ -0,0 +1,183 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::config::{Plugin, PluginFilterConfig, load_config};
+use crate::git::head_commit_sha;
+use crate::paths::{Paths, paths};
+use crate::sync::resolve::resolve_source_file;
+use crate::upgrade::lock_sha256_file as sha256_file;
+
+/// Pinned plugin revisions and source-file digests, persisted to
+/// `config.lock` (see [`Paths::lock`]) so plugin environments are
+/// reproducible across machines, in the spirit of `Cargo.lock`.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+pub struct LockFile {
+    #[serde(default)]
+    pub plugin: Vec<LockedPlugin>,
+}
+
+/// A single plugin's pinned state, keyed by `repo` (matching [`Plugin::repo`]).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LockedPlugin {
+    pub repo: String,
+    /// The full resolved commit SHA checked out when this entry was written.
+    pub rev: String,
+    /// SHA-256 digest of the resolved source file (see [`resolve_source_file`]),
+    /// or `None` for `type = "fpath"` plugins, which have no single source file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_digest: Option<String>,
+}
+
+impl LockFile {
+    /// Find the locked entry for a plugin by its `repo`, if one exists.
+    pub fn find(&self, repo: &str) -> Option<&LockedPlugin> {
+        self.plugin.iter().find(|e| e.repo == repo)
+    }
+}
+
+/// Load `config.lock`, or an empty [`LockFile`] if it doesn't exist yet
+/// (e.g. before the first `rz sync`/`rz lock`).
+///
+/// # Errors
+/// Returns an error if the file exists but can't be read or parsed.
+pub(crate) fn load_lock(p: &Paths) -> Result<LockFile> {
+    if !p.lock.is_file() {
+        return Ok(LockFile::default());
+    }
+    let txt = fs::read_to_string(&p.lock)
+        .with_context(|| format!("failed to read {}", p.lock.display()))?;
+    toml::from_str(&txt).with_context(|| format!("failed to parse {}", p.lock.display()))
+}
+
+/// Write `lock` to `config.lock`, creating its parent directory if needed.
+///
+/// # Errors
+/// Returns an error if serialization or the write fails.
+pub(crate) fn save_lock(p: &Paths, lock: &LockFile) -> Result<()> {
+    if let Some(parent) = p.lock.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let txt = toml::to_string_pretty(lock).context("failed to serialize config.lock")?;
+    fs::write(&p.lock, txt).with_context(|| format!("failed to write {}", p.lock.display()))
+}
+
+/// Compute the current [`LockedPlugin`] entry for an already-synced plugin.
+///
+/// # Errors
+/// Returns an error if `repo_dir`'s `HEAD` commit can't be resolved.
+pub(crate) fn locked_plugin_for(
+    pl: &Plugin,
+    repo_dir: &Path,
+    filter: &PluginFilterConfig,
+) -> Result<LockedPlugin> {
+    let rev = head_commit_sha(repo_dir)?;
+    let file_digest = if pl.r#type.as_deref() == Some("fpath") {
+        None
+    } else {
+        resolve_source_file(repo_dir, pl.file.as_deref(), filter)
+            .ok()
+            .and_then(|f| sha256_file(&f).ok())
+    };
+    Ok(LockedPlugin {
+        repo: pl.repo.clone(),
+        rev,
+        file_digest,
+    })
+}
+
+/// CLI command: regenerate `config.lock` from the current state of every
+/// already-synced plugin (`rz sync` must run first for an unsynced plugin
+/// to be lockable).
+///
+/// Unlike the locking that happens automatically as part of `rz sync`, this
+/// recomputes every entry unconditionally — useful after editing
+/// `config.toml` by hand, or to re-pin after resolving a checksum mismatch.
+/// `source = "local"` plugins are skipped, since they have no commit to pin.
+///
+/// # Errors
+/// Returns an error if `config.toml` can't be loaded, or the lockfile can't
+/// be written.
+pub fn cmd_lock() -> Result<()> {
+    let cfg = load_config()?;
+    let p = paths()?;
+
+    let mut lock = LockFile::default();
+    for pl in &cfg.plugins {
+        if pl.is_local() || pl.repo.trim().is_empty() {
+            continue;
+        }
+        let repo_dir = pl.content_dir(&p);
+        if !repo_dir.is_dir() {
+            continue;
+        }
+        lock.plugin
+            .push(locked_plugin_for(pl, &repo_dir, &cfg.plugin_filter)?);
+    }
+
+    let count = lock.plugin.len();
+    save_lock(&p, &lock)?;
+    println!("locked {count} plugin(s) into {}", p.lock.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn paths_under(root: &Path) -> Paths {
+        Paths {
+            bin: root.join("bin"),
+            plugins: root.join("plugins"),
+            repos: root.join("repos"),
+            cache: root.join("cache"),
+            config: root.join("config.toml"),
+            lock: root.join("config.lock"),
+        }
+    }
+
+    #[test]
+    fn load_lock_returns_empty_when_missing() {
+        let tmp = tempdir().unwrap();
+        let p = paths_under(tmp.path());
+        let lock = load_lock(&p).unwrap();
+        assert!(lock.plugin.is_empty());
+    }
+
+    #[test]
+    fn save_and_load_lock_roundtrips() {
+        let tmp = tempdir().unwrap();
+        let p = paths_under(tmp.path());
+
+        let mut lock = LockFile::default();
+        lock.plugin.push(LockedPlugin {
+            repo: "owner/repo".to_string(),
+            rev: "deadbeef".repeat(5),
+            file_digest: Some("abc123".to_string()),
+        });
+        lock.plugin.push(LockedPlugin {
+            repo: "owner/fpath-repo".to_string(),
+            rev: "cafebabe".repeat(5),
+            file_digest: None,
+        });
+
+        save_lock(&p, &lock).unwrap();
+        let loaded = load_lock(&p).unwrap();
+        assert_eq!(loaded, lock);
+    }
+
+    #[test]
+    fn lock_file_find_matches_by_repo() {
+        let lock = LockFile {
+            plugin: vec![LockedPlugin {
+                repo: "owner/repo".to_string(),
+                rev: "deadbeef".to_string(),
+                file_digest: None,
+            }],
+        };
+        assert!(lock.find("owner/repo").is_some());
+        assert!(lock.find("owner/other").is_none());
+    }
+}
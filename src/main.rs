@@ -5,16 +5,28 @@
 //! Features:
 //! - Manage plugins defined in `$(rz home)/.rz/config.toml`
 //! - `rz init` prints initialization code for `.zshrc`
-//! - `rz sync` clones or updates configured plugins
+//! - `rz sync` clones or updates configured plugins, pinning `config.lock`
+//! - `rz lock` regenerates `config.lock` from already-synced plugins
+//! - `rz compile` concatenates synced plugin sources into a single cached init script
+//! - `rz status` reports plugins that are behind their remote, without syncing
 //! - `rz upgrade` updates rz itself to the latest release
-//! - `rz list` show plugins in the effective load order with source/type metadata
+//! - `rz list` show plugins with source/type metadata (`--format json` for scripting)
+//! - `rz order` prints plugins in the effective load order
 //! - `rz home` prints the rz home directory
+//! - `rz completions <shell>` prints a shell completion script
+//! - User-defined `[aliases]` in config.toml expand to a sequence of the
+//!   above subcommands, e.g. `refresh = ["sync", "compile"]`
 //!
 //! This CLI is built with [clap](https://docs.rs/clap).
 
-use anyhow::Result;
-use clap::{Parser, Subcommand};
-use rz::{cmd_init, cmd_list, cmd_sync, cmd_upgrade, rz_home};
+use anyhow::{Context, Result, bail};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use std::io;
+
+use rz::{
+    ListFormat, cmd_compile, cmd_init, cmd_list, cmd_lock, cmd_order, cmd_status, cmd_sync,
+    cmd_upgrade, cmd_upgrade_rollback, load_config, rz_home,
+};
 
 /// Command-line interface definition.
 ///
@@ -39,30 +51,199 @@ enum Cmd {
     /// Print initialization code for .zshrc
     Init,
     /// Clone/update plugins defined in config.toml
-    Sync,
+    Sync {
+        /// Only reproduce plugins already pinned in config.lock, refusing to
+        /// sync anything without a lock entry
+        #[arg(long)]
+        locked: bool,
+    },
+    /// Regenerate config.lock from the currently synced plugins
+    Lock,
+    /// Concatenate synced plugin sources into a single cached init script
+    Compile,
+    /// Show plugins that are behind their remote, without syncing
+    Status,
     /// Update rat-zsh itself to the latest release
-    Upgrade,
+    Upgrade {
+        /// Fail instead of warning when the release has no checksum asset
+        #[arg(long)]
+        require_checksum: bool,
+        /// Restore the previous binary from its `.bak` backup instead of upgrading
+        #[arg(long)]
+        rollback: bool,
+    },
     /// List parsed plugins
-    List,
+    List {
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: ListFormatArg,
+    },
     /// Show plugins in the effective load order with source/type metadata
+    Order,
+    /// Print the rz home directory
     Home,
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to generate the completion script for
+        #[arg(value_enum)]
+        shell: CompletionShell,
+    },
+}
+
+/// Shells supported by `rz completions`.
+///
+/// A thin wrapper around [`clap_complete::Shell`] that also covers Nushell
+/// (generated via the separate `clap_complete_nushell` crate, which has no
+/// `clap_complete::Shell` variant of its own).
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+    PowerShell,
+    Elvish,
+    Nushell,
+}
+
+/// Output formats for `rz list`, as exposed on the CLI.
+///
+/// A thin wrapper around [`rz::ListFormat`], following the same pattern as
+/// [`CompletionShell`] — clap's `ValueEnum` stays confined to `main.rs`.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum ListFormatArg {
+    Text,
+    Json,
+}
+
+impl From<ListFormatArg> for ListFormat {
+    fn from(arg: ListFormatArg) -> Self {
+        match arg {
+            ListFormatArg::Text => ListFormat::Text,
+            ListFormatArg::Json => ListFormat::Json,
+        }
+    }
 }
 
 /// CLI entry point.
 ///
-/// Parses arguments with `clap` and executes the selected subcommand.
+/// Parses arguments with `clap` and executes the selected subcommand. If the
+/// first argument isn't a recognized subcommand, consults `config.toml`'s
+/// `[aliases]` table (see [`expand_and_run_alias`]) before giving up,
+/// mirroring the way `cargo` expands a user-defined alias into a real
+/// command before dispatch.
 fn main() -> Result<()> {
-    let cli = Cli::parse();
-    let cmd = cli.cmd.unwrap();
+    let args: Vec<String> = std::env::args().collect();
+
+    match Cli::try_parse_from(&args) {
+        Ok(cli) => run(cli.cmd.unwrap()),
+        Err(err) if err.kind() == clap::error::ErrorKind::InvalidSubcommand => {
+            match expand_and_run_alias(&args) {
+                Ok(()) => Ok(()),
+                Err(_) => err.exit(),
+            }
+        }
+        Err(err) => err.exit(),
+    }
+}
 
+/// Run a single parsed subcommand.
+fn run(cmd: Cmd) -> Result<()> {
     match cmd {
         Cmd::Init => cmd_init(),
-        Cmd::Sync => cmd_sync(),
-        Cmd::Upgrade => cmd_upgrade(),
-        Cmd::List => cmd_list(),
+        Cmd::Sync { locked } => cmd_sync(locked),
+        Cmd::Lock => cmd_lock(),
+        Cmd::Compile => cmd_compile(),
+        Cmd::Status => cmd_status(),
+        Cmd::Upgrade {
+            require_checksum,
+            rollback,
+        } => {
+            if rollback {
+                cmd_upgrade_rollback()
+            } else {
+                cmd_upgrade(require_checksum)
+            }
+        }
+        Cmd::List { format } => cmd_list(format.into()),
+        Cmd::Order => cmd_order(),
         Cmd::Home => {
             println!("{}", rz_home()?.display());
             Ok(())
         }
+        Cmd::Completions { shell } => {
+            print_completions(shell);
+            Ok(())
+        }
+    }
+}
+
+/// Generate a completion script for `shell` and print it to stdout.
+///
+/// Uses the `Cli` definition itself (via [`clap::CommandFactory`]) as the
+/// source of truth, so completions stay in sync with the subcommands above
+/// automatically.
+fn print_completions(shell: CompletionShell) {
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    match shell {
+        CompletionShell::Bash => {
+            clap_complete::generate(clap_complete::Shell::Bash, &mut cmd, name, &mut io::stdout())
+        }
+        CompletionShell::Zsh => {
+            clap_complete::generate(clap_complete::Shell::Zsh, &mut cmd, name, &mut io::stdout())
+        }
+        CompletionShell::Fish => {
+            clap_complete::generate(clap_complete::Shell::Fish, &mut cmd, name, &mut io::stdout())
+        }
+        CompletionShell::PowerShell => clap_complete::generate(
+            clap_complete::Shell::PowerShell,
+            &mut cmd,
+            name,
+            &mut io::stdout(),
+        ),
+        CompletionShell::Elvish => clap_complete::generate(
+            clap_complete::Shell::Elvish,
+            &mut cmd,
+            name,
+            &mut io::stdout(),
+        ),
+        CompletionShell::Nushell => clap_complete::generate(
+            clap_complete_nushell::Nushell,
+            &mut cmd,
+            name,
+            &mut io::stdout(),
+        ),
+    }
+}
+
+/// Expand `args[1]` as a `config.toml` `[aliases]` entry and run each
+/// resulting subcommand in order, forwarding any trailing args (after the
+/// alias name) to every expanded command.
+///
+/// # Errors
+/// Returns an error if there's no subcommand argument, no config, no
+/// matching alias, or any expanded subcommand fails to parse or run.
+fn expand_and_run_alias(args: &[String]) -> Result<()> {
+    let name = args.get(1).context("no subcommand given")?;
+    let cfg = load_config().context("no config.toml to resolve aliases from")?;
+    let expansion = cfg
+        .aliases
+        .get(name)
+        .with_context(|| format!("unrecognized subcommand or alias: {name}"))?;
+
+    if expansion.is_empty() {
+        bail!("alias \"{name}\" expands to no subcommands");
+    }
+
+    let extra_args = &args[2..];
+    for sub in expansion {
+        let mut argv = vec![args[0].clone()];
+        argv.extend(sub.split_whitespace().map(str::to_string));
+        argv.extend(extra_args.iter().cloned());
+
+        let cli = Cli::try_parse_from(&argv)
+            .with_context(|| format!("alias \"{name}\" expansion \"{sub}\" is not valid"))?;
+        run(cli.cmd.unwrap())?;
     }
+    Ok(())
 }
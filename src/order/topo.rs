@@ -0,0 +1,296 @@
+use anyhow::{Result, bail};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::config::Plugin;
+
+/// Plugins that load last by convention when the user hasn't set their own
+/// `priority`, preserving the historical hardcoded tail-plugin order from
+/// before `priority` existed.
+const DEFAULT_TAIL_REPOS: [&str; 2] = [
+    "zsh-users/zsh-autosuggestions",
+    "zsh-users/zsh-syntax-highlighting",
+];
+
+/// The implicit priority given to [`DEFAULT_TAIL_REPOS`] plugins that don't
+/// set their own `priority`. Any explicit `priority`, even `0`, overrides this.
+const DEFAULT_TAIL_PRIORITY: i64 = 1_000_000;
+
+/// A plugin's identifier for `after`/`before` references: its configured
+/// `name`, or its `repo` if no name is set — or, for `source = "local"`
+/// plugins that leave `repo` empty, its `path`.
+pub(crate) fn plugin_id(pl: &Plugin) -> &str {
+    pl.name.as_deref().unwrap_or_else(|| {
+        if pl.repo.is_empty() {
+            pl.path.as_deref().unwrap_or("")
+        } else {
+            &pl.repo
+        }
+    })
+}
+
+/// A plugin's effective sort priority (see `Plugin::priority`): its own
+/// value if set, otherwise [`DEFAULT_TAIL_PRIORITY`] for
+/// [`DEFAULT_TAIL_REPOS`], otherwise `0`.
+fn effective_priority(pl: &Plugin) -> i64 {
+    pl.priority.unwrap_or_else(|| {
+        if DEFAULT_TAIL_REPOS.contains(&pl.repo.as_str()) {
+            DEFAULT_TAIL_PRIORITY
+        } else {
+            0
+        }
+    })
+}
+
+/// Resolve the source order of `plugins` via a topological sort over each
+/// plugin's `after`/`before` edges (Kahn's algorithm), with `priority`
+/// breaking ties among plugins that have no unresolved dependencies left.
+///
+/// - An `after` entry on a plugin adds an edge from the referenced plugin
+///   to it (the referenced plugin is sourced first).
+/// - A `before` entry adds an edge from the plugin to the referenced one.
+/// - References that don't match any configured plugin (by [`plugin_id`])
+///   are ignored.
+/// - Among plugins ready to be placed at any given step (zero remaining
+///   in-degree), the one with the lowest [`effective_priority`] goes next,
+///   ties broken alphabetically by [`plugin_id`]. This lets any plugin be
+///   deterministically forced to load last (or anywhere in between) via
+///   `priority`, not just the two [`DEFAULT_TAIL_REPOS`] built-ins, while
+///   explicit `after`/`before` edges are always honored first.
+///
+/// # Returns
+/// The indices of `plugins` in resolved order.
+///
+/// # Errors
+/// Returns an error if the `after`/`before` edges form a cycle.
+pub(crate) fn resolve_order(plugins: &[Plugin]) -> Result<Vec<usize>> {
+    let n = plugins.len();
+    let ids: Vec<&str> = plugins.iter().map(plugin_id).collect();
+    let priorities: Vec<i64> = plugins.iter().map(effective_priority).collect();
+    let index_of: HashMap<&str, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+    let mut adj: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut indegree: Vec<usize> = vec![0; n];
+
+    for (v, pl) in plugins.iter().enumerate() {
+        for after in &pl.after {
+            if let Some(&u) = index_of.get(after.as_str()) {
+                adj[u].push(v);
+                indegree[v] += 1;
+            }
+        }
+        for before in &pl.before {
+            if let Some(&w) = index_of.get(before.as_str()) {
+                adj[v].push(w);
+                indegree[w] += 1;
+            }
+        }
+    }
+
+    let mut ready: BinaryHeap<Reverse<(i64, &str, usize)>> = (0..n)
+        .filter(|&i| indegree[i] == 0)
+        .map(|i| Reverse((priorities[i], ids[i], i)))
+        .collect();
+
+    let mut order = Vec::with_capacity(n);
+    while let Some(Reverse((_, _, u))) = ready.pop() {
+        order.push(u);
+        for &v in &adj[u] {
+            indegree[v] -= 1;
+            if indegree[v] == 0 {
+                ready.push(Reverse((priorities[v], ids[v], v)));
+            }
+        }
+    }
+
+    if order.len() != n {
+        let stuck: Vec<&str> = (0..n)
+            .filter(|&i| indegree[i] > 0)
+            .map(|i| ids[i])
+            .collect();
+        bail!(
+            "cycle detected in plugin after/before order: {}",
+            stuck.join(", ")
+        );
+    }
+
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plugin(repo: &str, name: Option<&str>, after: &[&str], before: &[&str]) -> Plugin {
+        plugin_with_priority(repo, name, after, before, None)
+    }
+
+    fn plugin_with_priority(
+        repo: &str,
+        name: Option<&str>,
+        after: &[&str],
+        before: &[&str],
+        priority: Option<i64>,
+    ) -> Plugin {
+        Plugin {
+            source: String::new(),
+            repo: repo.to_string(),
+            path: None,
+            branch: None,
+            tag: None,
+            rev: None,
+            file: None,
+            r#use: Vec::new(),
+            r#type: None,
+            name: name.map(str::to_string),
+            fpath_dirs: Vec::new(),
+            autodetect: false,
+            hosts: Vec::new(),
+            not_hosts: Vec::new(),
+            os: Vec::new(),
+            apply: None,
+            priority,
+            depth: None,
+            after: after.iter().map(|s| s.to_string()).collect(),
+            before: before.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn ids_in_order(plugins: &[Plugin], order: &[usize]) -> Vec<String> {
+        order
+            .iter()
+            .map(|&i| plugin_id(&plugins[i]).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn resolve_order_sorts_unconfigured_plugins_alphabetically() {
+        let plugins = vec![
+            plugin("owner/zeta", None, &[], &[]),
+            plugin("owner/alpha", None, &[], &[]),
+        ];
+        let order = resolve_order(&plugins).unwrap();
+        assert_eq!(
+            ids_in_order(&plugins, &order),
+            vec!["owner/alpha", "owner/zeta"]
+        );
+    }
+
+    #[test]
+    fn resolve_order_honors_after_edge() {
+        let plugins = vec![
+            plugin("owner/a", None, &["owner/b"], &[]),
+            plugin("owner/b", None, &[], &[]),
+        ];
+        let order = resolve_order(&plugins).unwrap();
+        assert_eq!(ids_in_order(&plugins, &order), vec!["owner/b", "owner/a"]);
+    }
+
+    #[test]
+    fn resolve_order_honors_before_edge() {
+        let plugins = vec![
+            plugin("owner/a", None, &[], &["owner/b"]),
+            plugin("owner/b", None, &[], &[]),
+        ];
+        let order = resolve_order(&plugins).unwrap();
+        assert_eq!(ids_in_order(&plugins, &order), vec!["owner/a", "owner/b"]);
+    }
+
+    #[test]
+    fn resolve_order_matches_by_name_when_set() {
+        let plugins = vec![
+            plugin("owner/a", Some("first"), &["second"], &[]),
+            plugin("owner/b", Some("second"), &[], &[]),
+        ];
+        let order = resolve_order(&plugins).unwrap();
+        assert_eq!(ids_in_order(&plugins, &order), vec!["second", "first"]);
+    }
+
+    #[test]
+    fn resolve_order_defaults_tail_plugins_last() {
+        let plugins = vec![
+            plugin(
+                "zsh-users/zsh-syntax-highlighting",
+                None,
+                &[],
+                &[],
+            ),
+            plugin("owner/zeta", None, &[], &[]),
+            plugin(
+                "zsh-users/zsh-autosuggestions",
+                None,
+                &[],
+                &[],
+            ),
+        ];
+        let order = resolve_order(&plugins).unwrap();
+        assert_eq!(
+            ids_in_order(&plugins, &order),
+            vec![
+                "owner/zeta",
+                "zsh-users/zsh-autosuggestions",
+                "zsh-users/zsh-syntax-highlighting",
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_order_explicit_after_edge_overrides_default_tail_priority() {
+        let plugins = vec![
+            plugin("owner/zeta", None, &["zsh-users/zsh-autosuggestions"], &[]),
+            plugin("zsh-users/zsh-autosuggestions", None, &[], &[]),
+        ];
+        let order = resolve_order(&plugins).unwrap();
+        assert_eq!(
+            ids_in_order(&plugins, &order),
+            vec!["zsh-users/zsh-autosuggestions", "owner/zeta"]
+        );
+    }
+
+    #[test]
+    fn resolve_order_explicit_priority_forces_any_plugin_last() {
+        let plugins = vec![
+            plugin_with_priority("owner/alpha", None, &[], &[], None),
+            plugin_with_priority("owner/zeta", None, &[], &[], Some(5)),
+            plugin_with_priority("owner/beta", None, &[], &[], None),
+        ];
+        let order = resolve_order(&plugins).unwrap();
+        assert_eq!(
+            ids_in_order(&plugins, &order),
+            vec!["owner/alpha", "owner/beta", "owner/zeta"]
+        );
+    }
+
+    #[test]
+    fn resolve_order_explicit_priority_overrides_default_tail_repo() {
+        let plugins = vec![
+            plugin_with_priority("zsh-users/zsh-autosuggestions", None, &[], &[], Some(-1)),
+            plugin_with_priority("owner/zeta", None, &[], &[], None),
+        ];
+        let order = resolve_order(&plugins).unwrap();
+        assert_eq!(
+            ids_in_order(&plugins, &order),
+            vec!["zsh-users/zsh-autosuggestions", "owner/zeta"]
+        );
+    }
+
+    #[test]
+    fn resolve_order_ignores_unknown_references() {
+        let plugins = vec![plugin("owner/a", None, &["no-such-plugin"], &[])];
+        let order = resolve_order(&plugins).unwrap();
+        assert_eq!(ids_in_order(&plugins, &order), vec!["owner/a"]);
+    }
+
+    #[test]
+    fn resolve_order_detects_cycle() {
+        let plugins = vec![
+            plugin("owner/a", None, &["owner/b"], &[]),
+            plugin("owner/b", None, &["owner/a"], &[]),
+        ];
+        let err = resolve_order(&plugins).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("cycle detected"));
+        assert!(msg.contains("owner/a"));
+        assert!(msg.contains("owner/b"));
+    }
+}
@@ -1,103 +1,189 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use directories::{BaseDirs, ProjectDirs};
 use std::{env, ffi::OsString, path::PathBuf};
 
 /// Holds important directory paths used by rat-zsh.
 ///
 /// - `bin`: directory where the `rz` binary is placed
-/// - `plugins`: directory where plugin files are stored
+/// - `plugins`: directory where plugin symlinks are stored
 /// - `repos`: directory where plugin repositories are cloned
+/// - `cache`: directory for caches and in-progress downloads (e.g. `rz upgrade`)
 /// - `config`: path to the `config.toml` configuration file
+/// - `lock`: path to the `config.lock` lockfile (see [`crate::lock`]),
+///   alongside `config.toml`
 #[derive(Clone)]
 pub struct Paths {
     pub bin: PathBuf,
     pub plugins: PathBuf,
     pub repos: PathBuf,
+    pub cache: PathBuf,
     pub config: PathBuf,
+    pub lock: PathBuf,
 }
 
-/// Compute the rat-zsh home directory from the given environment variables.
+/// Collapse every directory under a single root.
 ///
-/// Behavior:
-/// - If `xdg` is set, base is `<xdg>`.
-/// - Otherwise, base is `<home>`.
+/// This is the layout used when `$RZ_HOME` is set, and matches rat-zsh's
+/// original pre-XDG-layering behavior for users who prefer one directory.
+fn collapse_under(root: PathBuf) -> Paths {
+    Paths {
+        bin: root.join("bin"),
+        plugins: root.join("plugins"),
+        repos: root.join("repos"),
+        cache: root.join("cache"),
+        config: root.join("config.toml"),
+        lock: root.join("config.lock"),
+    }
+}
+
+/// Resolve directories from platform-conventional locations via
+/// [`ProjectDirs`]: `config.toml` under the config dir, `repos`/`plugins`/`bin`
+/// under the data dir (they're persistent state, not disposable), and
+/// `cache` under the cache dir.
 ///
-/// In both cases, `".rz"` is appended at the end.
-fn rz_home_from_env(xdg: Option<OsString>, home: Option<OsString>) -> PathBuf {
-    let base = xdg
-        .map(PathBuf::from)
-        .unwrap_or_else(|| PathBuf::from(home.unwrap_or_default()));
-    base.join(".rz")
+/// On Linux this honors `XDG_CONFIG_HOME`/`XDG_DATA_HOME`/`XDG_CACHE_HOME`
+/// (falling back to their `~/.config`, `~/.local/share`, `~/.cache`
+/// defaults); macOS and Windows get their own conventional locations.
+fn from_project_dirs(dirs: &ProjectDirs) -> Paths {
+    let data = dirs.data_dir();
+    Paths {
+        bin: data.join("bin"),
+        plugins: data.join("plugins"),
+        repos: data.join("repos"),
+        cache: dirs.cache_dir().to_path_buf(),
+        config: dirs.config_dir().join("config.toml"),
+        lock: dirs.config_dir().join("config.lock"),
+    }
 }
 
-/// Return the rat-zsh home directory based on the current process environment.
+/// Returns a `Paths` struct with the resolved directories used by rat-zsh.
 ///
 /// Resolution order:
-/// 1. If `$XDG_CONFIG_HOME` is set, use `$XDG_CONFIG_HOME/.rz`.
-/// 2. Otherwise, use `$HOME/.rz`.
+/// 1. If `$RZ_HOME` is set, every directory collapses under that single
+///    root (see [`collapse_under`]).
+/// 2. Otherwise, if a legacy `~/.rz` directory already exists on disk
+///    (from before XDG layering was added), keep using it rather than
+///    splitting an existing install across the new locations (see
+///    [`legacy_rz_home`]).
+/// 3. Otherwise, resolve each directory through the OS's conventional
+///    locations via [`ProjectDirs`] (see [`from_project_dirs`]).
+///
+/// # Errors
+/// Returns an error if `$RZ_HOME` is unset, no legacy `~/.rz` exists, and
+/// the platform's home directory cannot be determined.
+pub fn paths() -> Result<Paths> {
+    paths_from_env(env::var_os("RZ_HOME"), legacy_rz_home())
+}
+
+/// The legacy, pre-XDG `~/.rz` directory, if it already exists on disk.
+/// Returns `None` if the home directory can't be determined or `~/.rz`
+/// isn't there — in which case [`paths_from_env`] falls through to the
+/// platform-conventional locations instead.
+fn legacy_rz_home() -> Option<PathBuf> {
+    let dir = BaseDirs::new()?.home_dir().join(".rz");
+    dir.is_dir().then_some(dir)
+}
+
+fn paths_from_env(rz_home: Option<OsString>, legacy_home: Option<PathBuf>) -> Result<Paths> {
+    if let Some(home) = rz_home {
+        return Ok(collapse_under(PathBuf::from(home)));
+    }
+    if let Some(legacy) = legacy_home {
+        return Ok(collapse_under(legacy));
+    }
+    let dirs = ProjectDirs::from("", "", "rz")
+        .context("could not determine home directory for platform base dirs")?;
+    Ok(from_project_dirs(&dirs))
+}
+
+/// Return the rat-zsh home directory: `$RZ_HOME` if set, otherwise an
+/// existing legacy `~/.rz`, otherwise the platform data directory where
+/// `repos/`, `plugins/`, and `bin/` live.
+///
+/// Note that `config.toml` and caches may live elsewhere when `$RZ_HOME`
+/// is unset — see [`paths`] for the full, per-purpose directory layout.
 pub fn rz_home() -> Result<PathBuf> {
-    Ok(rz_home_from_env(
-        env::var_os("XDG_CONFIG_HOME"),
-        env::var_os("HOME"),
-    ))
+    Ok(paths()?.bin.parent().unwrap().to_path_buf())
 }
 
-/// Returns a `Paths` struct with the resolved directories used by rat-zsh.
+/// Expand a leading `~` or `~/...` in `path` to the user's home directory,
+/// via [`directories::BaseDirs`]. Paths that don't start with `~`, or where
+/// the home directory can't be determined, are returned unchanged.
 ///
-/// This includes:
-/// - `bin` (`rz_home()/bin`)
-/// - `plugins` (`rz_home()/plugins`)
-/// - `repos` (`rz_home()/repos`)
-/// - `config` (`rz_home()/config.toml`)
-pub fn paths() -> Result<Paths> {
-    let home = rz_home()?;
-    Ok(Paths {
-        bin: home.join("bin"),
-        plugins: home.join("plugins"),
-        repos: home.join("repos"),
-        config: home.join("config.toml"),
-    })
+/// Used for `source = "local"` plugins' `path` (see
+/// [`crate::config::Plugin::content_dir`]), which point at an arbitrary
+/// directory rather than something under `~/.rz`.
+pub(crate) fn expand_tilde(path: &str) -> PathBuf {
+    if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(base) = BaseDirs::new() {
+            return base.home_dir().join(rest);
+        }
+    } else if path == "~"
+        && let Some(base) = BaseDirs::new()
+    {
+        return base.home_dir().to_path_buf();
+    }
+    PathBuf::from(path)
 }
 
 #[cfg(test)]
 mod tests {
-    use std::path::Path;
-
-    use tempfile::tempdir;
+    use super::*;
 
-    use crate::paths::Paths;
+    #[test]
+    fn collapse_under_builds_expected_layout() {
+        let p = collapse_under(PathBuf::from("/home/user/.rz"));
+        assert_eq!(p.bin, PathBuf::from("/home/user/.rz/bin"));
+        assert_eq!(p.plugins, PathBuf::from("/home/user/.rz/plugins"));
+        assert_eq!(p.repos, PathBuf::from("/home/user/.rz/repos"));
+        assert_eq!(p.cache, PathBuf::from("/home/user/.rz/cache"));
+        assert_eq!(p.config, PathBuf::from("/home/user/.rz/config.toml"));
+        assert_eq!(p.lock, PathBuf::from("/home/user/.rz/config.lock"));
+    }
 
-    fn paths_under(home: &Path) -> Paths {
-        Paths {
-            bin: home.join("bin"),
-            plugins: home.join("plugins"),
-            repos: home.join("repos"),
-            config: home.join("config.toml"),
-        }
+    #[test]
+    fn paths_from_env_prefers_rz_home_override() {
+        let p = paths_from_env(Some(OsString::from("/opt/rz-home")), None).unwrap();
+        assert_eq!(p.bin, PathBuf::from("/opt/rz-home/bin"));
+        assert_eq!(p.config, PathBuf::from("/opt/rz-home/config.toml"));
     }
 
     #[test]
-    fn rz_home_prefers_xdg_when_present() {
-        let xdg = tempdir().unwrap();
-        let home = tempdir().unwrap();
+    fn paths_from_env_rz_home_override_wins_over_legacy_dir() {
+        let p = paths_from_env(
+            Some(OsString::from("/opt/rz-home")),
+            Some(PathBuf::from("/home/user/.rz")),
+        )
+        .unwrap();
+        assert_eq!(p.bin, PathBuf::from("/opt/rz-home/bin"));
+    }
 
-        let got = super::rz_home_from_env(Some(xdg.path().into()), Some(home.path().into()));
-        assert_eq!(got, xdg.path().join(".rz"));
+    #[test]
+    fn paths_from_env_falls_back_to_legacy_dir_when_rz_home_unset() {
+        let p = paths_from_env(None, Some(PathBuf::from("/home/user/.rz"))).unwrap();
+        assert_eq!(p.bin, PathBuf::from("/home/user/.rz/bin"));
+        assert_eq!(p.config, PathBuf::from("/home/user/.rz/config.toml"));
     }
 
+
     #[test]
-    fn rz_home_falls_back_to_home() {
-        let home = tempdir().unwrap();
-        let got = super::rz_home_from_env(None, Some(home.path().into()));
-        assert_eq!(got, home.path().join(".rz"));
+    fn expand_tilde_leaves_non_tilde_paths_unchanged() {
+        assert_eq!(
+            expand_tilde("/abs/path/plugin"),
+            PathBuf::from("/abs/path/plugin")
+        );
+        assert_eq!(expand_tilde("relative/plugin"), PathBuf::from("relative/plugin"));
     }
 
     #[test]
-    fn paths_under_builds_expected() {
-        let base = tempdir().unwrap();
-        let p = paths_under(&base.path().join(".rz"));
-        assert_eq!(p.bin, base.path().join(".rz/bin"));
-        assert_eq!(p.plugins, base.path().join(".rz/plugins"));
-        assert_eq!(p.repos, base.path().join(".rz/repos"));
-        assert_eq!(p.config, base.path().join(".rz/config.toml"));
+    fn expand_tilde_expands_home_relative_paths() {
+        let Some(base) = BaseDirs::new() else {
+            return;
+        };
+        assert_eq!(
+            expand_tilde("~/dev/my-plugin"),
+            base.home_dir().join("dev/my-plugin")
+        );
+        assert_eq!(expand_tilde("~"), base.home_dir());
     }
 }
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use indicatif::{MultiProgress, ProgressBar};
+use rayon::prelude::*;
+use std::time::Duration;
+
+use crate::config::load_config;
+use crate::git::{GitReference, UpdateStatus, update_status};
+use crate::paths::paths;
+use crate::sync::progress::{err_style, ok_style, spinner_style};
+
+/// CLI command: report whether configured plugins are behind their remote,
+/// without touching the checked-out working tree.
+///
+/// For each plugin this fetches `origin` (refs only), then compares the
+/// checked-out `HEAD` against the resolved target (the pinned branch, tag,
+/// or rev, or the default branch — see [`crate::git::GitReference`]) via
+/// [`crate::git::update_status`]. Unlike `rz sync`, no reset or checkout is
+/// ever performed.
+///
+/// Checks run **in parallel** across a rayon thread pool (mirroring
+/// [`crate::sync::cmd_sync`]'s clone/fetch parallelism), since each one is
+/// its own network round-trip; output is still printed in config order, via
+/// a dedicated spinner per plugin.
+///
+/// `source = "local"` plugins (see [`crate::config::Plugin::is_local`]) have
+/// no remote to compare against and are skipped entirely.
+///
+/// # Errors
+/// Returns an error if `config.toml` cannot be loaded or parsed.
+pub fn cmd_status() -> Result<()> {
+    let p = paths()?;
+    let cfg = load_config()?;
+    if cfg.plugins.is_empty() {
+        eprintln!("no plugins in {}", p.config.display());
+        return Ok(());
+    }
+
+    let checks: Vec<(String, std::path::PathBuf, GitReference)> = cfg
+        .plugins
+        .iter()
+        .filter(|pl| !pl.is_local() && !pl.repo.trim().is_empty())
+        .map(|pl| (pl.display_name(), pl.content_dir(&p), pl.git_reference()))
+        .collect();
+
+    let mp = MultiProgress::new();
+    let run_style = spinner_style();
+
+    let mut bars: Vec<ProgressBar> = Vec::with_capacity(checks.len());
+    for (display, _, _) in &checks {
+        let pb = mp.add(ProgressBar::new_spinner());
+        pb.set_style(run_style.clone());
+        pb.set_message(format!("checking {display}"));
+        pb.enable_steady_tick(Duration::from_millis(80));
+        bars.push(pb);
+    }
+
+    checks
+        .par_iter()
+        .zip(bars.par_iter())
+        .for_each(|((display, repo_dir, git_ref), pb)| {
+            if !repo_dir.join(".git").exists() {
+                pb.set_style(err_style());
+                pb.finish_with_message(format!("{display} (not cloned yet)"));
+                return;
+            }
+
+            match update_status(repo_dir, git_ref) {
+                Ok(status) => {
+                    pb.set_style(if status.up_to_date() {
+                        ok_style()
+                    } else {
+                        err_style()
+                    });
+                    pb.finish_with_message(format!("{display} ({})", describe(&status)));
+                }
+                Err(e) => {
+                    pb.set_style(err_style());
+                    pb.finish_with_message(format!("{display} (error: {e})"));
+                }
+            }
+        });
+
+    Ok(())
+}
+
+/// Render an [`UpdateStatus`] as a short human-readable suffix.
+fn describe(status: &UpdateStatus) -> String {
+    if status.unknown {
+        return "target revision unresolved".to_string();
+    }
+    if status.dirty {
+        return "local changes".to_string();
+    }
+    if status.detached && status.up_to_date() {
+        return "detached, up to date".to_string();
+    }
+    match (status.behind, status.ahead) {
+        (0, 0) => "up to date".to_string(),
+        (behind, 0) => format!("behind by {behind}"),
+        (0, ahead) => format!("ahead by {ahead}"),
+        (behind, ahead) => format!("diverged: {ahead} ahead, {behind} behind"),
+    }
+}
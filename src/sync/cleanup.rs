@@ -1,11 +1,13 @@
 use anyhow::Result;
 use indicatif::{MultiProgress, ProgressBar};
+use rayon::prelude::*;
 use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::fs;
-use std::path::{Component, Path};
+use std::path::{Component, Path, PathBuf};
 use std::time::Duration;
 
+use super::keep_patterns::KeepMatcher;
 use super::progress::{err_style, ok_style, spinner_style};
 
 /// Remove stale plugin entries from the plugin directory.
@@ -20,6 +22,8 @@ use super::progress::{err_style, ok_style, spinner_style};
 /// - `mp`: `MultiProgress` instance for rendering multiple progress bars.
 /// - `plugins_dir`: Path to the plugins directory (`~/.rz/plugins`).
 /// - `expect`: Set of plugin names that should exist (all others are considered stale).
+/// - `keep`: Compiled `[cleanup] keep` patterns (see [`KeepMatcher`]); an
+///   entry matching any of them is protected exactly like an `expect` hit.
 ///
 /// # Errors
 /// Returns `Err` if filesystem operations fail (other than "not found").
@@ -27,6 +31,7 @@ pub fn cleanup_stale_plugins(
     mp: &MultiProgress,
     plugins_dir: &Path,
     expect: &HashSet<String>,
+    keep: &KeepMatcher,
 ) -> Result<()> {
     let rd = match fs::read_dir(plugins_dir) {
         Ok(rd) => rd,
@@ -38,6 +43,10 @@ pub fn cleanup_stale_plugins(
         if expect.contains(&name) {
             continue;
         }
+        let is_dir = ent.file_type().map(|ft| ft.is_dir()).unwrap_or(false);
+        if keep.is_kept(Path::new(&name), is_dir) {
+            continue;
+        }
 
         let pb = mp.add(ProgressBar::new_spinner());
         pb.set_style(spinner_style());
@@ -72,6 +81,8 @@ pub fn cleanup_stale_plugins(
 /// - `repos_dir`: Path to the repos directory (`~/.rz/repos`).
 /// - `expect_slugs`: Set of repository slugs that should exist.
 /// - `plugins_dir`: Path to the plugins directory (used to detect symlink targets).
+/// - `keep`: Compiled `[cleanup] keep` patterns (see [`KeepMatcher`]); a slug
+///   matching any of them is protected exactly like an `expect_slugs` hit.
 ///
 /// # Errors
 /// Returns `Err` if filesystem operations fail (other than "not found").
@@ -80,7 +91,95 @@ pub fn cleanup_stale_repos(
     repos_dir: &Path,
     expect_slugs: &HashSet<String>,
     plugins_dir: &Path,
+    keep: &KeepMatcher,
+) -> Result<()> {
+    for (slug, path) in stale_repo_slugs(repos_dir, expect_slugs, plugins_dir, keep) {
+        let pb = mp.add(ProgressBar::new_spinner());
+        pb.set_style(spinner_style());
+        pb.set_message(format!("removing stale repo: {}", slug));
+        pb.enable_steady_tick(Duration::from_millis(80));
+        remove_repo(&pb, &slug, &path);
+    }
+    Ok(())
+}
+
+/// Like [`cleanup_stale_repos`], but removes stale repos across `jobs`
+/// worker threads instead of one at a time, for large `repos` trees where
+/// sequential `fs::remove_dir_all` calls are noticeably slow.
+///
+/// Each stale repo gets its own progress bar on the shared `mp`, updated by
+/// whichever worker removes it (`indicatif`'s `MultiProgress` is
+/// `Send + Sync`); bars are *added* to `mp` in stale-slug order before any
+/// removal starts, so the final print order is deterministic regardless of
+/// which worker finishes first. `jobs == 1`
+/// degrades to the sequential [`cleanup_stale_repos`] path (and `jobs == 0`
+/// is treated the same way, rather than building a zero-thread pool).
+///
+/// # Errors
+/// Returns `Err` only if the worker pool fails to build; a failed removal
+/// is reported on its progress bar instead.
+pub fn cleanup_stale_repos_parallel(
+    mp: &MultiProgress,
+    repos_dir: &Path,
+    expect_slugs: &HashSet<String>,
+    plugins_dir: &Path,
+    keep: &KeepMatcher,
+    jobs: usize,
 ) -> Result<()> {
+    if jobs <= 1 {
+        return cleanup_stale_repos(mp, repos_dir, expect_slugs, plugins_dir, keep);
+    }
+
+    let stale = stale_repo_slugs(repos_dir, expect_slugs, plugins_dir, keep);
+
+    let mut bars: Vec<ProgressBar> = Vec::with_capacity(stale.len());
+    for (slug, _) in &stale {
+        let pb = mp.add(ProgressBar::new_spinner());
+        pb.set_style(spinner_style());
+        pb.set_message(format!("removing stale repo: {}", slug));
+        pb.enable_steady_tick(Duration::from_millis(80));
+        bars.push(pb);
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(jobs).build()?;
+    pool.install(|| {
+        stale
+            .par_iter()
+            .zip(bars.par_iter())
+            .for_each(|((slug, path), pb)| remove_repo(pb, slug, path));
+    });
+
+    Ok(())
+}
+
+/// Remove a single stale repo directory, updating its progress bar with the
+/// outcome. Shared by [`cleanup_stale_repos`] and [`cleanup_stale_repos_parallel`].
+fn remove_repo(pb: &ProgressBar, slug: &str, path: &Path) {
+    match fs::remove_dir_all(path) {
+        Ok(_) => {
+            pb.set_style(ok_style());
+            pb.finish_with_message(format!("removed repo: {slug}"));
+        }
+        Err(e) => {
+            pb.set_style(err_style());
+            pb.finish_with_message(format!("remove repo {slug} (error: {e})"));
+        }
+    }
+}
+
+/// Compute the slugs (and paths) under `repos_dir` that are stale: not in
+/// `expect_slugs`, not symlinked-to from `plugins_dir` (the `in_use` set,
+/// built once up front), and not protected by a `keep` pattern.
+///
+/// The `in_use` set and the candidate list are both built here so that
+/// [`cleanup_stale_repos_parallel`] can fan the actual removals out across
+/// workers without each one re-scanning `plugins_dir`.
+fn stale_repo_slugs(
+    repos_dir: &Path,
+    expect_slugs: &HashSet<String>,
+    plugins_dir: &Path,
+    keep: &KeepMatcher,
+) -> Vec<(String, PathBuf)> {
     let mut in_use: HashSet<String> = HashSet::new();
     if let Ok(rd) = fs::read_dir(plugins_dir) {
         for ent in rd.flatten() {
@@ -92,31 +191,22 @@ pub fn cleanup_stale_repos(
         }
     }
 
-    if let Ok(rd) = fs::read_dir(repos_dir) {
-        for ent in rd.flatten() {
-            let slug = ent.file_name().to_string_lossy().to_string();
-            if expect_slugs.contains(&slug) || in_use.contains(&slug) {
-                continue;
-            }
+    let Ok(rd) = fs::read_dir(repos_dir) else {
+        return Vec::new();
+    };
 
-            let pb = mp.add(ProgressBar::new_spinner());
-            pb.set_style(spinner_style());
-            pb.set_message(format!("removing stale repo: {}", slug));
-            pb.enable_steady_tick(Duration::from_millis(80));
-
-            match fs::remove_dir_all(ent.path()) {
-                Ok(_) => {
-                    pb.set_style(ok_style());
-                    pb.finish_with_message(format!("removed repo: {}", slug));
-                }
-                Err(e) => {
-                    pb.set_style(err_style());
-                    pb.finish_with_message(format!("remove repo {} (error: {})", slug, e));
-                }
+    rd.flatten()
+        .filter_map(|ent| {
+            let slug = ent.file_name().to_string_lossy().to_string();
+            if expect_slugs.contains(&slug)
+                || in_use.contains(&slug)
+                || keep.is_kept(Path::new(&slug), true)
+            {
+                return None;
             }
-        }
-    }
-    Ok(())
+            Some((slug, ent.path()))
+        })
+        .collect()
 }
 
 /// Extract the slug (repository identifier) from a plugin symlink target.
@@ -171,12 +261,38 @@ mod tests {
         expect.insert("keep.plugin.zsh".to_string());
 
         let mp = MultiProgress::new();
-        cleanup_stale_plugins(&mp, &plugins_dir, &expect).unwrap();
+        let keep_patterns = KeepMatcher::compile(&[]);
+        cleanup_stale_plugins(&mp, &plugins_dir, &expect, &keep_patterns).unwrap();
 
         assert!(keep.exists());
         assert!(!drop_.exists(), "stale plugin should be removed");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn cleanup_stale_plugins_keeps_entries_matching_keep_pattern() {
+        let tmp = tempfile::tempdir().unwrap();
+        let plugins_dir = tmp.path().join("plugins");
+        fs::create_dir(&plugins_dir).unwrap();
+
+        let protected = plugins_dir.join("local-dev.plugin.zsh");
+        fs::File::create(&protected)
+            .unwrap()
+            .write_all(b"ok")
+            .unwrap();
+
+        let drop_ = plugins_dir.join("drop.plugin.zsh");
+        fs::File::create(&drop_).unwrap().write_all(b"ng").unwrap();
+
+        let expect = HashSet::new();
+        let mp = MultiProgress::new();
+        let keep_patterns = KeepMatcher::compile(&["local-*".to_string()]);
+        cleanup_stale_plugins(&mp, &plugins_dir, &expect, &keep_patterns).unwrap();
+
+        assert!(protected.exists(), "entry matching a keep pattern must survive");
+        assert!(!drop_.exists(), "stale plugin not matching any keep pattern should be removed");
+    }
+
     #[cfg(unix)]
     #[test]
     fn cleanup_stale_repos_removes_unused_and_unexpected_repos() {
@@ -204,7 +320,8 @@ mod tests {
         let expect_slugs: HashSet<String> = HashSet::new();
 
         let mp = MultiProgress::new();
-        cleanup_stale_repos(&mp, &repos_dir, &expect_slugs, &plugins_dir).unwrap();
+        let keep_patterns = KeepMatcher::compile(&[]);
+        cleanup_stale_repos(&mp, &repos_dir, &expect_slugs, &plugins_dir, &keep_patterns).unwrap();
 
         assert!(usedslug.exists(), "in-use repo must be preserved");
         assert!(
@@ -213,6 +330,100 @@ mod tests {
         );
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn cleanup_stale_repos_keeps_slugs_matching_keep_pattern() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repos_dir = tmp.path().join("repos");
+        let plugins_dir = tmp.path().join("plugins");
+        fs::create_dir(&repos_dir).unwrap();
+        fs::create_dir(&plugins_dir).unwrap();
+
+        let protected = repos_dir.join("local-dev-repo");
+        fs::create_dir(&protected).unwrap();
+        fs::File::create(protected.join("x")).unwrap();
+
+        let staleslug = repos_dir.join("staleslug");
+        fs::create_dir(&staleslug).unwrap();
+        fs::File::create(staleslug.join("x")).unwrap();
+
+        let expect_slugs: HashSet<String> = HashSet::new();
+        let mp = MultiProgress::new();
+        let keep_patterns = KeepMatcher::compile(&["local-*".to_string()]);
+        cleanup_stale_repos(&mp, &repos_dir, &expect_slugs, &plugins_dir, &keep_patterns).unwrap();
+
+        assert!(protected.exists(), "repo matching a keep pattern must survive");
+        assert!(!staleslug.exists(), "unmatched stale repo should still be removed");
+    }
+
+    #[cfg(unix)]
+    fn repo_fixture(jobs_count: usize) -> (tempfile::TempDir, PathBuf, PathBuf) {
+        let tmp = tempfile::tempdir().unwrap();
+        // `extract_slug` looks for a literal `repos` path component.
+        let root = tmp.path().join(format!("run-{jobs_count}"));
+        let repos_dir = root.join("repos");
+        let plugins_dir = root.join("plugins");
+        fs::create_dir(&repos_dir).unwrap();
+        fs::create_dir(&plugins_dir).unwrap();
+
+        let usedslug = repos_dir.join("usedslug");
+        fs::create_dir(&usedslug).unwrap();
+        let used_target = usedslug.join("some.zsh");
+        fs::File::create(&used_target)
+            .unwrap()
+            .write_all(b"echo ok")
+            .unwrap();
+        symlink_file(&used_target, &plugins_dir.join("some-plugin"));
+
+        for i in 0..4 {
+            let stale = repos_dir.join(format!("staleslug{i}"));
+            fs::create_dir(&stale).unwrap();
+            fs::File::create(stale.join("x")).unwrap();
+        }
+
+        let protected = repos_dir.join("local-dev-repo");
+        fs::create_dir(&protected).unwrap();
+        fs::File::create(protected.join("x")).unwrap();
+
+        (tmp, repos_dir, plugins_dir)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cleanup_stale_repos_parallel_matches_serial_result_regardless_of_worker_count() {
+        let expect_slugs: HashSet<String> = HashSet::new();
+        let keep_patterns = KeepMatcher::compile(&["local-*".to_string()]);
+
+        for jobs_count in [1, 2, 4, 8] {
+            let (_tmp, repos_dir, plugins_dir) = repo_fixture(jobs_count);
+            let mp = MultiProgress::new();
+            cleanup_stale_repos_parallel(
+                &mp,
+                &repos_dir,
+                &expect_slugs,
+                &plugins_dir,
+                &keep_patterns,
+                jobs_count,
+            )
+            .unwrap();
+
+            assert!(
+                repos_dir.join("usedslug").exists(),
+                "in-use repo must survive with jobs={jobs_count}"
+            );
+            assert!(
+                repos_dir.join("local-dev-repo").exists(),
+                "keep-pattern repo must survive with jobs={jobs_count}"
+            );
+            for i in 0..4 {
+                assert!(
+                    !repos_dir.join(format!("staleslug{i}")).exists(),
+                    "stale repo {i} must be removed with jobs={jobs_count}"
+                );
+            }
+        }
+    }
+
     #[test]
     fn extract_slug_returns_slug_after_repos_component() {
         let p = PathBuf::from("/home/user/.rz/repos/zsh-users__zsh-autosuggestions/file.zsh");
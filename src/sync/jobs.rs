@@ -1,7 +1,9 @@
 use std::collections::HashSet;
 use std::path::PathBuf;
 
-use crate::config::Config;
+use crate::config::{Config, Plugin};
+use crate::git::GitReference;
+use crate::lock::{LockFile, LockedPlugin};
 use crate::paths::Paths;
 
 /// Represents a single plugin synchronization job.
@@ -12,12 +14,25 @@ use crate::paths::Paths;
 #[derive(Clone)]
 pub struct SyncJob {
     pub display: String,
+    pub repo: String,
     pub url: String,
     pub repo_dir: PathBuf,
     pub link_path: PathBuf,
     pub kind_fpath: bool,
     pub file_hint: Option<String>,
-    pub rev: Option<String>,
+    pub git_ref: GitReference,
+    pub depth: Option<u32>,
+    /// This plugin's `config.lock` entry, if one exists. When present and
+    /// the plugin doesn't pin an explicit `branch`/`tag` of its own,
+    /// `git_ref` is overridden to check out `locked.rev` exactly, and the
+    /// resynced source file's digest is verified against `locked.file_digest`
+    /// (see [`crate::lock`]).
+    pub locked: Option<LockedPlugin>,
+    /// Whether this is a `source = "local"` plugin (see [`Plugin::is_local`]).
+    /// Local plugins skip the clone/fetch step entirely and are exempt from
+    /// `config.lock`, since `repo_dir` already points straight at the
+    /// user-given directory rather than something under `p.repos`.
+    pub local: bool,
 }
 
 /// Build synchronization jobs from the parsed configuration.
@@ -26,47 +41,404 @@ pub struct SyncJob {
 /// while also computing the expected plugin names and repository slugs.
 /// These are later used for cleanup (removing stale plugins/repos).
 ///
+/// Plugins whose `hosts`/`not_hosts`/`os` constraints don't match this
+/// machine (see [`plugin_active_on_this_machine`]) are skipped entirely —
+/// they don't become jobs and aren't added to the expected sets, so cleanup
+/// won't delete them as stale on a host where they're simply inactive.
+///
+/// When `lock` has an entry for a plugin (see [`crate::lock`]) and the
+/// plugin doesn't pin its own `branch`/`tag`, the job's `git_ref` is
+/// overridden to the locked commit so the sync reproduces exactly what was
+/// last locked, rather than whatever the tracked branch/rev currently
+/// resolves to.
+///
 /// # Arguments
 /// - `cfg`: The loaded configuration (`config.toml`).
 /// - `p`: Paths struct containing important directories (`bin`, `repos`, `plugins`, etc.).
+/// - `lock`: The loaded `config.lock` (see [`crate::lock::load_lock`]).
 ///
 /// # Returns
 /// A tuple of:
 /// - `Vec<SyncJob>`: List of jobs to execute during sync.
 /// - `HashSet<String>`: Expected plugin names (for symlinks).
 /// - `HashSet<String>`: Expected repo slugs (for cloned repos).
-pub fn build_jobs(cfg: &Config, p: &Paths) -> (Vec<SyncJob>, HashSet<String>, HashSet<String>) {
+pub fn build_jobs(
+    cfg: &Config,
+    p: &Paths,
+    lock: &LockFile,
+) -> (Vec<SyncJob>, HashSet<String>, HashSet<String>) {
     let mut expect_plugin_names = HashSet::new();
     let mut expect_repo_slugs = HashSet::new();
     let mut jobs: Vec<SyncJob> = Vec::new();
 
+    let hostname = current_hostname();
+
     for pl in &cfg.plugins {
-        if pl.repo.trim().is_empty() {
+        let is_local = pl.is_local();
+        if is_local {
+            if pl.path.as_deref().unwrap_or("").trim().is_empty() {
+                continue;
+            }
+        } else if pl.repo.trim().is_empty() {
+            continue;
+        }
+        if !plugin_active_on_this_machine(pl, &hostname) {
             continue;
         }
-        let slug = pl.repo.replace('/', "__");
-        let repo_dir = p.repos.join(&slug);
+        let slug = pl.slug();
+        let repo_dir = pl.content_dir(p);
         let plug_name = pl.name.as_deref().unwrap_or(&slug);
         let link = p.plugins.join(plug_name);
 
         expect_plugin_names.insert(plug_name.to_string());
-        expect_repo_slugs.insert(slug.clone());
+        if !is_local {
+            expect_repo_slugs.insert(slug.clone());
+        }
+
+        let url = if is_local {
+            String::new()
+        } else {
+            resolve_repo_url(&pl.source, &pl.repo)
+        };
+
+        let mut git_ref = pl.git_reference();
+        let locked = if is_local {
+            None
+        } else {
+            lock.find(&pl.repo).cloned()
+        };
+        if let Some(entry) = &locked
+            && matches!(git_ref, GitReference::Default | GitReference::Rev(_))
+        {
+            git_ref = GitReference::Rev(entry.rev.clone());
+        }
 
-        let url = match pl.source.as_str() {
-            "" | "github" => format!("https://github.com/{}.git", pl.repo),
-            other => other.to_string(),
+        // Plugins that always track the tip (no pinned branch/tag/rev)
+        // default to a shallow depth of 1 unless the user overrides it;
+        // pinned plugins fetch full history by default so arbitrary
+        // branches/tags/revs stay resolvable.
+        let auto_depth = if matches!(git_ref, GitReference::Default) {
+            Some(1)
+        } else {
+            None
         };
+        let depth = pl.depth.or(cfg.default_depth).or(auto_depth);
 
         jobs.push(SyncJob {
-            display: pl.name.clone().unwrap_or_else(|| pl.repo.clone()),
+            display: pl.display_name(),
+            repo: pl.repo.clone(),
             url,
             repo_dir,
             link_path: link,
             kind_fpath: pl.r#type.as_deref() == Some("fpath"),
             file_hint: pl.file.clone(),
-            rev: pl.rev.clone(),
+            git_ref,
+            depth,
+            locked,
+            local: is_local,
         });
     }
 
     (jobs, expect_plugin_names, expect_repo_slugs)
 }
+
+/// Expand a plugin's `source`/`repo` config into a clone URL.
+///
+/// `source` may be:
+/// - `""` or `"github"` → `https://github.com/<repo>.git`
+/// - `"gitlab"` → `https://gitlab.com/<repo>.git`
+/// - `"codeberg"` → `https://codeberg.org/<repo>.git`
+/// - `"bitbucket"` → `https://bitbucket.org/<repo>.git`
+/// - `"git"` → `repo` used verbatim as the clone URL (GitLab, Bitbucket,
+///   self-hosted — anything with no named shorthand above); its slug is
+///   derived from the URL's last path segment instead of `owner/repo`
+///   (see [`crate::config::Plugin::slug`]).
+/// - `"<host>:<owner>/<repo>"` → `https://<host>/<owner>/<repo>.git`, a
+///   generic shorthand for self-hosted forges (GitLab/Gitea/Forgejo
+///   instances) that have no named shorthand above; `repo` is ignored in
+///   this form since the path is already embedded in `source`.
+/// - anything else (e.g. a full URL, or a `git@host:owner/repo` SSH URL) →
+///   used as-is.
+fn resolve_repo_url(source: &str, repo: &str) -> String {
+    match source {
+        "" | "github" => format!("https://github.com/{repo}.git"),
+        "gitlab" => format!("https://gitlab.com/{repo}.git"),
+        "codeberg" => format!("https://codeberg.org/{repo}.git"),
+        "bitbucket" => format!("https://bitbucket.org/{repo}.git"),
+        "git" => repo.to_string(),
+        other if !other.contains("://") && !other.starts_with("git@") && other.contains(':') => {
+            let (host, path) = other.split_once(':').expect("checked above");
+            format!("https://{host}/{path}.git")
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Resolve the current machine's hostname for evaluating `hosts`/`not_hosts`
+/// constraints. Falls back to an empty string (matching nothing) if it can't
+/// be determined or isn't valid UTF-8.
+fn current_hostname() -> String {
+    hostname::get()
+        .ok()
+        .and_then(|h| h.into_string().ok())
+        .unwrap_or_default()
+}
+
+/// Evaluate a plugin's `hosts`/`not_hosts`/`os` constraints (see
+/// [`Plugin::hosts`]) against the current machine, so one shared
+/// `config.toml` can drive different hosts. Plugins with no constraints set
+/// are always active.
+fn plugin_active_on_this_machine(pl: &Plugin, hostname: &str) -> bool {
+    if !pl.hosts.is_empty() && !pl.hosts.iter().any(|h| h == hostname) {
+        return false;
+    }
+    if pl.not_hosts.iter().any(|h| h == hostname) {
+        return false;
+    }
+    if !pl.os.is_empty() && !pl.os.iter().any(|o| o == std::env::consts::OS) {
+        return false;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn resolve_repo_url_defaults_to_github() {
+        assert_eq!(
+            resolve_repo_url("", "zsh-users/zsh-autosuggestions"),
+            "https://github.com/zsh-users/zsh-autosuggestions.git"
+        );
+        assert_eq!(
+            resolve_repo_url("github", "zsh-users/zsh-autosuggestions"),
+            "https://github.com/zsh-users/zsh-autosuggestions.git"
+        );
+    }
+
+    #[test]
+    fn resolve_repo_url_supports_named_forge_shorthands() {
+        assert_eq!(
+            resolve_repo_url("gitlab", "owner/repo"),
+            "https://gitlab.com/owner/repo.git"
+        );
+        assert_eq!(
+            resolve_repo_url("codeberg", "owner/repo"),
+            "https://codeberg.org/owner/repo.git"
+        );
+        assert_eq!(
+            resolve_repo_url("bitbucket", "owner/repo"),
+            "https://bitbucket.org/owner/repo.git"
+        );
+    }
+
+    #[test]
+    fn resolve_repo_url_git_source_uses_repo_verbatim() {
+        assert_eq!(
+            resolve_repo_url("git", "https://gitlab.example.com/owner/repo.git"),
+            "https://gitlab.example.com/owner/repo.git"
+        );
+    }
+
+    #[test]
+    fn resolve_repo_url_supports_generic_host_shorthand() {
+        assert_eq!(
+            resolve_repo_url("git.example.com:owner/repo", "unused"),
+            "https://git.example.com/owner/repo.git"
+        );
+    }
+
+    #[test]
+    fn resolve_repo_url_passes_through_full_urls() {
+        assert_eq!(
+            resolve_repo_url("https://example.com/owner/repo.git", "unused"),
+            "https://example.com/owner/repo.git"
+        );
+        assert_eq!(
+            resolve_repo_url("git@example.com:owner/repo.git", "unused"),
+            "git@example.com:owner/repo.git"
+        );
+    }
+
+    fn plugin_with_constraints(hosts: &[&str], not_hosts: &[&str], os: &[&str]) -> Plugin {
+        Plugin {
+            source: String::new(),
+            repo: "owner/repo".to_string(),
+            path: None,
+            branch: None,
+            tag: None,
+            rev: None,
+            file: None,
+            r#use: Vec::new(),
+            r#type: None,
+            name: None,
+            fpath_dirs: Vec::new(),
+            autodetect: false,
+            hosts: hosts.iter().map(|s| s.to_string()).collect(),
+            not_hosts: not_hosts.iter().map(|s| s.to_string()).collect(),
+            os: os.iter().map(|s| s.to_string()).collect(),
+            apply: None,
+            priority: None,
+            depth: None,
+            after: Vec::new(),
+            before: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn plugin_active_on_this_machine_allows_unconstrained_plugins() {
+        let pl = plugin_with_constraints(&[], &[], &[]);
+        assert!(plugin_active_on_this_machine(&pl, "anyhost"));
+    }
+
+    #[test]
+    fn plugin_active_on_this_machine_requires_hosts_match() {
+        let pl = plugin_with_constraints(&["laptop"], &[], &[]);
+        assert!(plugin_active_on_this_machine(&pl, "laptop"));
+        assert!(!plugin_active_on_this_machine(&pl, "server"));
+    }
+
+    #[test]
+    fn plugin_active_on_this_machine_not_hosts_excludes_even_if_in_hosts() {
+        let pl = plugin_with_constraints(&["laptop"], &["laptop"], &[]);
+        assert!(!plugin_active_on_this_machine(&pl, "laptop"));
+    }
+
+    fn paths_under(root: &std::path::Path) -> Paths {
+        Paths {
+            bin: root.join("bin"),
+            plugins: root.join("plugins"),
+            repos: root.join("repos"),
+            cache: root.join("cache"),
+            config: root.join("config.toml"),
+            lock: root.join("config.lock"),
+        }
+    }
+
+    fn empty_config(plugins: Vec<Plugin>) -> Config {
+        Config {
+            plugins,
+            default_depth: None,
+            upgrade: Default::default(),
+            aliases: HashMap::new(),
+            templates: HashMap::new(),
+            cleanup: Default::default(),
+            jobs: None,
+            plugin_filter: Default::default(),
+        }
+    }
+
+    #[test]
+    fn build_jobs_skips_cloning_for_local_source_plugins() {
+        let tmp = tempfile::tempdir().unwrap();
+        let p = paths_under(tmp.path());
+        let local_dir = tmp.path().join("dev").join("my-plugin");
+        std::fs::create_dir_all(&local_dir).unwrap();
+
+        let pl = Plugin {
+            source: "local".to_string(),
+            repo: String::new(),
+            path: Some(local_dir.to_string_lossy().to_string()),
+            ..plugin_with_constraints(&[], &[], &[])
+        };
+        let cfg = empty_config(vec![pl]);
+        let lock = LockFile::default();
+        let (jobs, _, expect_repo_slugs) = build_jobs(&cfg, &p, &lock);
+
+        assert_eq!(jobs.len(), 1);
+        assert!(jobs[0].local);
+        assert_eq!(jobs[0].repo_dir, local_dir);
+        assert_eq!(jobs[0].display, "my-plugin");
+        assert!(expect_repo_slugs.is_empty());
+    }
+
+    #[test]
+    fn build_jobs_derives_slug_from_last_segment_for_git_source() {
+        let tmp = tempfile::tempdir().unwrap();
+        let p = paths_under(tmp.path());
+
+        let pl = Plugin {
+            source: "git".to_string(),
+            repo: "https://gitlab.example.com/owner/repo.git".to_string(),
+            ..plugin_with_constraints(&[], &[], &[])
+        };
+        let cfg = empty_config(vec![pl]);
+        let lock = LockFile::default();
+        let (jobs, _, expect_repo_slugs) = build_jobs(&cfg, &p, &lock);
+
+        assert_eq!(jobs.len(), 1);
+        assert!(!jobs[0].local);
+        assert_eq!(jobs[0].url, "https://gitlab.example.com/owner/repo.git");
+        assert_eq!(jobs[0].repo_dir, p.repos.join("repo"));
+        assert!(expect_repo_slugs.contains("repo"));
+    }
+
+    #[test]
+    fn build_jobs_depth_precedence_plugin_then_config_then_auto() {
+        let tmp = tempfile::tempdir().unwrap();
+        let p = paths_under(tmp.path());
+
+        // No pin at all: defaults to a shallow depth of 1.
+        let tracking = plugin_with_constraints(&[], &[], &[]);
+        // Same, but the plugin sets its own depth, which wins outright.
+        let pinned_depth = Plugin {
+            depth: Some(5),
+            ..plugin_with_constraints(&[], &[], &[])
+        };
+        // A pinned branch fetches full history by default (no auto depth).
+        let pinned_branch = Plugin {
+            branch: Some("main".to_string()),
+            ..plugin_with_constraints(&[], &[], &[])
+        };
+
+        let mut cfg = empty_config(vec![tracking, pinned_depth, pinned_branch]);
+        cfg.default_depth = Some(10);
+        let lock = LockFile::default();
+        let (jobs, _, _) = build_jobs(&cfg, &p, &lock);
+
+        assert_eq!(jobs.len(), 3);
+        assert_eq!(jobs[0].depth, Some(10), "config default_depth wins over auto_depth");
+        assert_eq!(jobs[1].depth, Some(5), "plugin depth wins over config default_depth");
+        assert_eq!(
+            jobs[2].depth,
+            Some(10),
+            "config default_depth still applies to a pinned branch"
+        );
+    }
+
+    #[test]
+    fn build_jobs_auto_depth_applies_only_with_no_config_default() {
+        let tmp = tempfile::tempdir().unwrap();
+        let p = paths_under(tmp.path());
+
+        let tracking = plugin_with_constraints(&[], &[], &[]);
+        let pinned_branch = Plugin {
+            branch: Some("main".to_string()),
+            ..plugin_with_constraints(&[], &[], &[])
+        };
+
+        let cfg = empty_config(vec![tracking, pinned_branch]);
+        let lock = LockFile::default();
+        let (jobs, _, _) = build_jobs(&cfg, &p, &lock);
+
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].depth, Some(1), "untracked default branch auto-shallows");
+        assert_eq!(jobs[1].depth, None, "pinned branch fetches full history");
+    }
+
+    #[test]
+    fn plugin_active_on_this_machine_requires_os_match() {
+        let other_os = if std::env::consts::OS == "linux" {
+            "macos"
+        } else {
+            "linux"
+        };
+        let pl = plugin_with_constraints(&[], &[], &[std::env::consts::OS]);
+        assert!(plugin_active_on_this_machine(&pl, "anyhost"));
+
+        let pl = plugin_with_constraints(&[], &[], &[other_os]);
+        assert!(!plugin_active_on_this_machine(&pl, "anyhost"));
+    }
+}
@@ -0,0 +1,173 @@
+//! Gitignore-style "keep"/"protect" pattern matching for `rz sync`'s cleanup
+//! step (see [`crate::sync::cleanup`] and [`crate::config::CleanupConfig`]).
+//!
+//! Patterns are compiled once into regexes by [`KeepMatcher::compile`], then
+//! matched against each entry's path *relative to the scanned root* (the
+//! plugins or repos directory) while iterating `read_dir`, rather than
+//! expanding the patterns into a file list up front.
+
+use regex::Regex;
+use std::path::Path;
+
+/// A single compiled keep pattern.
+struct Rule {
+    regex: Regex,
+    /// `true` if the pattern ended in `/`, matching directories only.
+    dir_only: bool,
+}
+
+/// A compiled set of gitignore-style keep patterns.
+///
+/// Supports `*` (any run of characters within one path segment), `**`
+/// (zero or more whole path segments), `?` (one character), `[...]`
+/// character classes, a leading `/` anchoring the pattern to the scanned
+/// root (instead of matching at any depth), and a trailing `/` restricting
+/// the match to directories.
+pub struct KeepMatcher {
+    rules: Vec<Rule>,
+}
+
+impl KeepMatcher {
+    /// Compile `patterns` (as given in `config.toml`'s `[cleanup] keep`)
+    /// into a matcher.
+    pub fn compile(patterns: &[String]) -> KeepMatcher {
+        let rules = patterns.iter().map(|p| compile_pattern(p)).collect();
+        KeepMatcher { rules }
+    }
+
+    /// Does `rel_path` (relative to the scanned root) match any compiled
+    /// pattern? `is_dir` excludes directory-only (trailing `/`) patterns
+    /// from matching non-directory entries.
+    pub fn is_kept(&self, rel_path: &Path, is_dir: bool) -> bool {
+        let Some(path_str) = rel_path.to_str() else {
+            return false;
+        };
+        self.rules
+            .iter()
+            .any(|r| (is_dir || !r.dir_only) && r.regex.is_match(path_str))
+    }
+}
+
+/// Compile a single gitignore-style pattern into a [`Rule`].
+fn compile_pattern(pat: &str) -> Rule {
+    let dir_only = pat.ends_with('/');
+    let trimmed = pat.strip_suffix('/').unwrap_or(pat);
+    let anchored = trimmed.starts_with('/');
+    let trimmed = trimmed.strip_prefix('/').unwrap_or(trimmed);
+
+    let mut body = String::from("^");
+    if !anchored {
+        body.push_str("(?:.*/)?");
+    }
+    body.push_str(&segment_to_regex(trimmed));
+    body.push('$');
+
+    Rule {
+        regex: Regex::new(&body).unwrap(),
+        dir_only,
+    }
+}
+
+/// Translate the body of a gitignore-style pattern (anchor/trailing-slash
+/// already stripped) into a regex fragment.
+fn segment_to_regex(pat: &str) -> String {
+    let chars: Vec<char> = pat.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                if chars.get(i) == Some(&'/') {
+                    i += 1;
+                    out.push_str("(?:.*/)?");
+                } else {
+                    out.push_str(".*");
+                }
+            }
+            '*' => {
+                out.push_str("[^/]*");
+                i += 1;
+            }
+            '?' => {
+                out.push_str("[^/]");
+                i += 1;
+            }
+            '.' => {
+                out.push_str("\\.");
+                i += 1;
+            }
+            '[' => {
+                out.push('[');
+                i += 1;
+                if chars.get(i) == Some(&'!') {
+                    out.push('^');
+                    i += 1;
+                }
+                while i < chars.len() && chars[i] != ']' {
+                    out.push(chars[i]);
+                    i += 1;
+                }
+                if i < chars.len() {
+                    out.push(']');
+                    i += 1;
+                }
+            }
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unanchored_pattern_matches_at_any_depth() {
+        let m = KeepMatcher::compile(&["local-*".to_string()]);
+        assert!(m.is_kept(Path::new("local-dev"), false));
+        assert!(m.is_kept(Path::new("sub/local-dev"), false));
+        assert!(!m.is_kept(Path::new("dev-local"), false));
+    }
+
+    #[test]
+    fn anchored_pattern_matches_only_at_root() {
+        let m = KeepMatcher::compile(&["/local-dev".to_string()]);
+        assert!(m.is_kept(Path::new("local-dev"), false));
+        assert!(!m.is_kept(Path::new("sub/local-dev"), false));
+    }
+
+    #[test]
+    fn double_star_matches_across_nested_slugs() {
+        let m = KeepMatcher::compile(&["**/my-dev-plugin".to_string()]);
+        assert!(m.is_kept(Path::new("my-dev-plugin"), false));
+        assert!(m.is_kept(Path::new("github.com/me/my-dev-plugin"), false));
+        assert!(!m.is_kept(Path::new("my-dev-plugin-extra"), false));
+    }
+
+    #[test]
+    fn trailing_slash_matches_directories_only() {
+        let m = KeepMatcher::compile(&["vendor/".to_string()]);
+        assert!(m.is_kept(Path::new("vendor"), true));
+        assert!(!m.is_kept(Path::new("vendor"), false));
+    }
+
+    #[test]
+    fn question_mark_and_character_class_match_one_character() {
+        let m = KeepMatcher::compile(&["rz-v?.plugin".to_string(), "[abc].zsh".to_string()]);
+        assert!(m.is_kept(Path::new("rz-v1.plugin"), false));
+        assert!(!m.is_kept(Path::new("rz-v12.plugin"), false));
+        assert!(m.is_kept(Path::new("a.zsh"), false));
+        assert!(!m.is_kept(Path::new("d.zsh"), false));
+    }
+
+    #[test]
+    fn no_patterns_keeps_nothing() {
+        let m = KeepMatcher::compile(&[]);
+        assert!(!m.is_kept(Path::new("anything"), false));
+    }
+}
@@ -1,18 +1,23 @@
 mod cleanup;
 mod jobs;
-mod progress;
-mod resolve;
+pub(crate) mod keep_patterns;
+pub(crate) mod progress;
+pub(crate) mod resolve;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use indicatif::{MultiProgress, ProgressBar};
 use rayon::prelude::*;
+use std::collections::HashSet;
 use std::fs;
 use std::time::Duration;
 
 use crate::config::load_config;
-use crate::git::ensure_repo;
+use crate::git::{RepoUpdate, ensure_repo, head_commit_sha};
+use crate::lock::{LockedPlugin, load_lock, save_lock};
 use crate::paths::paths;
-use crate::sync::cleanup::{cleanup_stale_plugins, cleanup_stale_repos};
+use crate::sync::cleanup::{cleanup_stale_plugins, cleanup_stale_repos_parallel};
+use crate::sync::keep_patterns::KeepMatcher;
+use crate::upgrade::lock_sha256_file as sha256_file;
 
 use progress::{err_style, ok_style, spinner_style};
 use resolve::{resolve_source_file, symlink};
@@ -21,15 +26,31 @@ use resolve::{resolve_source_file, symlink};
 ///
 /// High-level flow:
 /// 1. Ensure directory layout under `~/.rz` (`bin/`, `plugins/`, `repos/`, and the parent of `config.toml`).
-/// 2. Load configuration and build a list of jobs to run (see [`jobs::build_jobs`]).
+/// 2. Load configuration, `config.lock` (see [`crate::lock`]), and build a list of jobs to run (see [`jobs::build_jobs`]).
 /// 3. Run clone/fetch + link resolution **in parallel** with progress spinners.
 ///    - For `source`-type plugins: resolve the source file inside the repo (or use `file` hint) and symlink it.
 ///    - For `fpath`-type plugins: symlink the **directory** so it is appended to `fpath`.
-/// 4. Clean up stale plugin links and repositories that are no longer referenced (see [`cleanup`]).
+///    - A plugin with a `config.lock` entry is checked out at its locked commit (unless it
+///      pins its own `branch`/`tag`), and its resynced digest is verified against the locked
+///      one, failing that one job on a mismatch.
+///    - `source = "local"` plugins (see [`crate::config::Plugin::is_local`]) skip the
+///      clone/fetch step entirely and symlink straight into the given directory; they're
+///      exempt from `config.lock` since there's no commit to pin.
+/// 4. Clean up stale plugin links and repositories that are no longer referenced (see
+///    [`cleanup`]), skipping any entry matched by a `[cleanup] keep` pattern (see
+///    [`keep_patterns::KeepMatcher`]). Stale repo removal runs across `config.toml`'s
+///    `jobs` worker threads (defaulting to the detected CPU count when unset); see
+///    [`cleanup::cleanup_stale_repos_parallel`].
+/// 5. Unless `locked` is set, write `config.lock` back out with every successfully synced
+///    plugin's resulting revision/digest, so the next sync (especially `--locked`, elsewhere)
+///    reproduces it exactly.
 ///
-/// Progress reporting uses `indicatif::MultiProgress`; each job gets its own spinner.  
+/// When `locked` is `true`, any plugin with no `config.lock` entry fails immediately instead
+/// of being cloned/updated — `rz sync --locked` only ever reproduces what's already pinned.
+///
+/// Progress reporting uses `indicatif::MultiProgress`; each job gets its own spinner.
 /// Errors in individual jobs are captured and shown on the jobâ€™s line; processing continues for the rest.
-pub fn cmd_sync() -> Result<()> {
+pub fn cmd_sync(locked: bool) -> Result<()> {
     let p = paths()?;
     fs::create_dir_all(&p.bin)?;
     fs::create_dir_all(&p.plugins)?;
@@ -44,7 +65,8 @@ pub fn cmd_sync() -> Result<()> {
         return Ok(());
     }
 
-    let (jobs, expect_plugin_names, expect_repo_slugs) = jobs::build_jobs(&cfg, &p);
+    let lock = load_lock(&p)?;
+    let (jobs, expect_plugin_names, expect_repo_slugs) = jobs::build_jobs(&cfg, &p, &lock);
 
     let mp = MultiProgress::new();
     let run_style = spinner_style();
@@ -60,40 +82,147 @@ pub fn cmd_sync() -> Result<()> {
         bars.push(pb);
     }
 
-    jobs.par_iter().enumerate().for_each(|(idx, job)| {
-        let pb = &bars[idx];
-        let res: Result<()> = (|| {
-            ensure_repo(&job.url, &job.repo_dir, job.rev.as_deref())?;
+    let results: Vec<Result<(RepoUpdate, Option<LockedPlugin>)>> = jobs
+        .par_iter()
+        .enumerate()
+        .map(|(idx, job)| {
+            let pb = &bars[idx];
+            let res: Result<(RepoUpdate, Option<LockedPlugin>)> = (|| {
+                if locked && !job.local && job.locked.is_none() {
+                    bail!("not present in config.lock; run `rz lock` or drop --locked");
+                }
 
-            if job.link_path.exists() {
-                let _ = fs::remove_file(&job.link_path);
-            }
-            if job.kind_fpath {
-                symlink(&job.repo_dir, &job.link_path)?;
-            } else {
-                let src = resolve_source_file(&job.repo_dir, job.file_hint.as_deref())
+                let update = if job.local {
+                    RepoUpdate::Unchanged
+                } else {
+                    ensure_repo(&job.url, &job.repo_dir, &job.git_ref, job.depth)?
+                };
+
+                if job.link_path.exists() {
+                    let _ = fs::remove_file(&job.link_path);
+                }
+                let file_digest = if job.kind_fpath {
+                    symlink(&job.repo_dir, &job.link_path)?;
+                    None
+                } else {
+                    let src = resolve_source_file(
+                        &job.repo_dir,
+                        job.file_hint.as_deref(),
+                        &cfg.plugin_filter,
+                    )
                     .with_context(|| {
                         format!("no source file found in {}", job.repo_dir.display())
                     })?;
-                symlink(&src, &job.link_path)?;
-            }
-            Ok(())
-        })();
+                    symlink(&src, &job.link_path)?;
+                    Some(sha256_file(&src)?)
+                };
+
+                if let Some(locked_entry) = &job.locked
+                    && let (Some(expected), Some(actual)) =
+                        (&locked_entry.file_digest, &file_digest)
+                    && expected != actual
+                {
+                    bail!(
+                        "checksum mismatch (expected {expected}, got {actual}) — \
+                         possible tampering or corruption; resolve and re-run `rz lock`"
+                    );
+                }
 
-        match res {
-            Ok(_) => {
-                pb.set_style(done_style.clone());
-                pb.finish();
+                let entry = if job.local {
+                    None
+                } else {
+                    Some(LockedPlugin {
+                        repo: job.repo.clone(),
+                        rev: head_commit_sha(&job.repo_dir)?,
+                        file_digest,
+                    })
+                };
+                Ok((update, entry))
+            })();
+
+            match &res {
+                Ok((update, _)) => {
+                    pb.set_style(done_style.clone());
+                    pb.finish();
+                    print_changelog(pb, &job.display, update);
+                }
+                Err(e) => {
+                    pb.set_style(fail_style.clone());
+                    pb.finish_with_message(format!("syncing {} (error: {})", job.display, e));
+                }
             }
-            Err(e) => {
-                pb.set_style(fail_style.clone());
-                pb.finish_with_message(format!("syncing {} (error: {})", job.display, e));
+            res
+        })
+        .collect();
+
+    let keep = KeepMatcher::compile(&cfg.cleanup.keep);
+    cleanup_stale_plugins(&mp, &p.plugins, &expect_plugin_names, &keep)?;
+    let effective_jobs = cfg.jobs.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    });
+    cleanup_stale_repos_parallel(
+        &mp,
+        &p.repos,
+        &expect_repo_slugs,
+        &p.plugins,
+        &keep,
+        effective_jobs,
+    )?;
+
+    if !locked {
+        let known_repos: HashSet<&str> = cfg.plugins.iter().map(|pl| pl.repo.as_str()).collect();
+        let mut lock = lock;
+        lock.plugin.retain(|e| known_repos.contains(e.repo.as_str()));
+        for res in &results {
+            if let Ok((_, Some(entry))) = res {
+                lock.plugin.retain(|e| e.repo != entry.repo);
+                lock.plugin.push(entry.clone());
             }
         }
-    });
+        save_lock(&p, &lock)?;
+    }
 
-    cleanup_stale_plugins(&mp, &p.plugins, &expect_plugin_names)?;
-    cleanup_stale_repos(&mp, &p.repos, &expect_repo_slugs, &p.plugins)?;
+    let failed = results.iter().filter(|r| r.is_err()).count();
+    let ok = results.len() - failed;
+    if failed == 0 {
+        println!("synced {ok}/{} plugins", results.len());
+    } else {
+        println!("synced {ok}/{} plugins ({failed} failed)", results.len());
+    }
 
     Ok(())
 }
+
+/// Print a compact "updated A→B (N commits)" block for a plugin whose tip
+/// moved, with one line per commit underneath. `pb.println` is used so the
+/// block is inserted above the progress bars without corrupting their
+/// redraw.
+fn print_changelog(pb: &ProgressBar, display: &str, update: &RepoUpdate) {
+    match update {
+        RepoUpdate::Cloned | RepoUpdate::Unchanged => {}
+        RepoUpdate::Diverged {
+            old_short,
+            new_short,
+        } => {
+            pb.println(format!("{display} updated {old_short}→{new_short} (force)"));
+        }
+        RepoUpdate::Commits {
+            old_short,
+            new_short,
+            commits,
+        } => {
+            if commits.is_empty() {
+                return;
+            }
+            pb.println(format!(
+                "{display} updated {old_short}→{new_short} ({} commits)",
+                commits.len()
+            ));
+            for c in commits {
+                pb.println(format!("  {} {}", c.short_sha, c.summary));
+            }
+        }
+    }
+}
@@ -20,3 +20,13 @@ pub fn ok_style() -> ProgressStyle {
 pub fn err_style() -> ProgressStyle {
     ProgressStyle::with_template("\x1b[31m✘\x1b[0m {wide_msg}").unwrap()
 }
+
+/// Style used for byte-level download progress.
+/// - Cyan/blue bar with current/total bytes, transfer rate, and ETA.
+pub fn download_bar_style() -> ProgressStyle {
+    ProgressStyle::with_template(
+        "{wide_msg}\n\x1b[36m{bar:40.cyan/blue}\x1b[0m {bytes}/{total_bytes} ({bytes_per_sec}, eta {eta})",
+    )
+    .unwrap()
+    .progress_chars("=> ")
+}
@@ -3,6 +3,8 @@ use regex::Regex;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::config::PluginFilterConfig;
+
 /// Create a symbolic link from `src` to `dst`.
 ///
 /// This implementation is Unix-only. On non-Unix systems,
@@ -15,8 +17,10 @@ pub fn symlink(src: &Path, dst: &Path) -> Result<()> {
 /// Resolve the actual plugin source file within a repository.
 ///
 /// Search order:
-/// 1. If `hint` is provided and points to an existing file, use it.
-/// 2. Otherwise, try to find a file matching one of these patterns:
+/// 1. If `hint` is provided and points to an existing file, use it (`hint`
+///    is never subject to `filter`, since it's an explicit, user-given path).
+/// 2. Otherwise, try to find a file matching one of these patterns, skipping
+///    any candidate `filter` rejects (see [`PluginFilterConfig::is_extension_allowed`]):
 ///    - `*.plugin.zsh`
 ///    - `*.zsh`
 ///    - `*.zsh-theme`
@@ -24,7 +28,11 @@ pub fn symlink(src: &Path, dst: &Path) -> Result<()> {
 /// Returns:
 /// - The first file that matches.
 /// - Error if no valid file is found.
-pub fn resolve_source_file(repo_dir: &Path, hint: Option<&str>) -> Result<PathBuf> {
+pub fn resolve_source_file(
+    repo_dir: &Path,
+    hint: Option<&str>,
+    filter: &PluginFilterConfig,
+) -> Result<PathBuf> {
     if let Some(rel) = hint {
         let p = repo_dir.join(rel);
         if p.is_file() {
@@ -32,41 +40,114 @@ pub fn resolve_source_file(repo_dir: &Path, hint: Option<&str>) -> Result<PathBu
         }
     }
     for pat in ["*.plugin.zsh", "*.zsh", "*.zsh-theme"] {
-        if let Some(p) = glob1(repo_dir, pat) {
+        if let Some(p) = glob1(repo_dir, pat, filter) {
             return Ok(p);
         }
     }
     Err(anyhow!("no plugin file matched"))
 }
 
-/// Find the first file in a directory that matches a glob-like pattern.
-/// Only supports simple wildcards (`*`) and dots (`.`).
-fn glob1(dir: &Path, pat: &str) -> Option<PathBuf> {
+/// Resolve every file a plugin should source, from `patterns` (its `use`
+/// glob patterns, see [`crate::config::Plugin::r#use`]) if any are given,
+/// falling back to [`resolve_source_file`]'s single-file `hint`-then-default
+/// behavior otherwise.
+///
+/// Each pattern is matched with [`glob_all`] (so `lib/*.zsh` matches within
+/// that one subdirectory), filtered by `filter` (see
+/// [`PluginFilterConfig::is_extension_allowed`]), and the combined matches
+/// across all patterns are deduplicated and sorted for a stable order.
+///
+/// # Errors
+/// Returns an error if `patterns` is non-empty but none of them match
+/// anything, or (falling back) if [`resolve_source_file`] fails.
+pub fn resolve_source_files(
+    repo_dir: &Path,
+    hint: Option<&str>,
+    patterns: &[String],
+    filter: &PluginFilterConfig,
+) -> Result<Vec<PathBuf>> {
+    if patterns.is_empty() {
+        return resolve_source_file(repo_dir, hint, filter).map(|f| vec![f]);
+    }
+
+    let mut matches: Vec<PathBuf> = patterns
+        .iter()
+        .flat_map(|pat| glob_all(repo_dir, pat, filter))
+        .collect();
+    matches.sort();
+    matches.dedup();
+    if matches.is_empty() {
+        return Err(anyhow!("no plugin file matched"));
+    }
+    Ok(matches)
+}
+
+/// Find the first file in a directory that matches a glob-like pattern and
+/// is accepted by `filter`.
+fn glob1(dir: &Path, pat: &str, filter: &PluginFilterConfig) -> Option<PathBuf> {
+    glob1_all(dir, pat, filter).into_iter().next()
+}
+
+/// Find every file in a directory that matches a glob-like pattern and is
+/// accepted by `filter`, sorted for a stable order.
+fn glob1_all(dir: &Path, pat: &str, filter: &PluginFilterConfig) -> Vec<PathBuf> {
     let re = glob_to_regex(pat);
-    fs::read_dir(dir)
-        .ok()?
+    let mut matches: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
         .filter_map(|e| e.ok())
         .map(|e| e.path())
-        .find(|p| {
+        .filter(|p| p.is_file())
+        .filter(|p| {
             p.file_name()
                 .and_then(|s| s.to_str())
-                .map(|s| re.is_match(s))
+                .map(|s| re.is_match(s) && filter.is_extension_allowed(s))
                 .unwrap_or(false)
         })
+        .collect();
+    matches.sort();
+    matches
+}
+
+/// Find every file matching a glob-like pattern under `dir`, accepted by
+/// `filter`. A pattern containing a `/` (e.g. `lib/*.zsh`) matches within
+/// that one subdirectory of `dir`, rather than `dir` itself; only one level
+/// of nesting is supported.
+fn glob_all(dir: &Path, pat: &str, filter: &PluginFilterConfig) -> Vec<PathBuf> {
+    match pat.split_once('/') {
+        Some((subdir, rest)) => glob1_all(&dir.join(subdir), rest, filter),
+        None => glob1_all(dir, pat, filter),
+    }
 }
 
 /// Convert a minimal glob pattern into a regular expression.
 /// Supported:
-/// - `*` → `.*`
+/// - `*` → zero or more characters (`.*`)
+/// - `?` → exactly one character (`.`)
+/// - `{a,b,c}` → brace alternation (`(?:a|b|c)`)
 /// - `.` → escaped as `\.`
 ///
 /// Other characters are copied literally.
 fn glob_to_regex(pat: &str) -> Regex {
     let mut s = String::from("^");
-    for ch in pat.chars() {
+    let mut chars = pat.chars();
+    while let Some(ch) = chars.next() {
         match ch {
             '*' => s.push_str(".*"),
+            '?' => s.push('.'),
             '.' => s.push_str("\\."),
+            '{' => {
+                s.push_str("(?:");
+                for alt_ch in chars.by_ref() {
+                    match alt_ch {
+                        '}' => break,
+                        ',' => s.push('|'),
+                        '.' => s.push_str("\\."),
+                        c => s.push(c),
+                    }
+                }
+                s.push(')');
+            }
             c => s.push(c),
         }
     }
@@ -92,6 +173,87 @@ mod tests {
         assert!(!re2.is_match("bar.zsh"));
     }
 
+    #[test]
+    fn glob_to_regex_supports_question_mark_and_braces() {
+        let re = glob_to_regex("?.zsh");
+        assert!(re.is_match("a.zsh"));
+        assert!(!re.is_match("ab.zsh"));
+
+        let re2 = glob_to_regex("{foo,bar}.zsh");
+        assert!(re2.is_match("foo.zsh"));
+        assert!(re2.is_match("bar.zsh"));
+        assert!(!re2.is_match("baz.zsh"));
+    }
+
+    #[test]
+    fn resolve_source_files_matches_every_pattern_sorted_and_deduped() {
+        let tmp = tempdir().unwrap();
+        let repo = tmp.path();
+
+        fs::write(repo.join("b.zsh"), "# b").unwrap();
+        fs::write(repo.join("a.zsh"), "# a").unwrap();
+        fs::write(repo.join("c.theme.zsh"), "# c").unwrap();
+
+        let patterns = vec!["*.zsh".to_string(), "a.zsh".to_string()];
+        let got = resolve_source_files(repo, None, &patterns, &PluginFilterConfig::default()).unwrap();
+        assert_eq!(
+            got,
+            vec![repo.join("a.zsh"), repo.join("b.zsh"), repo.join("c.theme.zsh")]
+        );
+    }
+
+    #[test]
+    fn resolve_source_files_matches_one_level_of_subdirectories() {
+        let tmp = tempdir().unwrap();
+        let repo = tmp.path();
+
+        let lib = repo.join("lib");
+        fs::create_dir_all(&lib).unwrap();
+        fs::write(lib.join("one.zsh"), "# one").unwrap();
+        fs::write(lib.join("two.zsh"), "# two").unwrap();
+        fs::write(repo.join("top.zsh"), "# top").unwrap();
+
+        let patterns = vec!["lib/*.zsh".to_string()];
+        let got = resolve_source_files(repo, None, &patterns, &PluginFilterConfig::default()).unwrap();
+        assert_eq!(got, vec![lib.join("one.zsh"), lib.join("two.zsh")]);
+    }
+
+    #[test]
+    fn resolve_source_files_errors_when_patterns_given_but_none_match() {
+        let tmp = tempdir().unwrap();
+        let repo = tmp.path();
+        let patterns = vec!["*.nope".to_string()];
+        let err =
+            resolve_source_files(repo, None, &patterns, &PluginFilterConfig::default()).unwrap_err();
+        assert!(err.to_string().contains("no plugin file matched"));
+    }
+
+    #[test]
+    fn resolve_source_files_falls_back_to_resolve_source_file_when_no_patterns() {
+        let tmp = tempdir().unwrap();
+        let repo = tmp.path();
+        fs::write(repo.join("a.zsh"), "# a").unwrap();
+
+        let got = resolve_source_files(repo, None, &[], &PluginFilterConfig::default()).unwrap();
+        assert_eq!(got, vec![repo.join("a.zsh")]);
+    }
+
+    #[test]
+    fn resolve_source_files_skips_entries_rejected_by_filter() {
+        let tmp = tempdir().unwrap();
+        let repo = tmp.path();
+        fs::write(repo.join("a.zsh"), "# a").unwrap();
+        fs::write(repo.join("a.md"), "# readme").unwrap();
+
+        let filter = PluginFilterConfig {
+            allowed_extensions: Vec::new(),
+            excluded_extensions: vec!["md".to_string()],
+        };
+        let patterns = vec!["*".to_string()];
+        let got = resolve_source_files(repo, None, &patterns, &filter).unwrap();
+        assert_eq!(got, vec![repo.join("a.zsh")]);
+    }
+
     #[test]
     fn resolve_uses_hint_when_valid() {
         let tmp = tempdir().unwrap();
@@ -101,7 +263,9 @@ mod tests {
         fs::create_dir_all(hinted.parent().unwrap()).unwrap();
         fs::write(&hinted, "# hint").unwrap();
 
-        let got = resolve_source_file(repo, Some("subdir/my.zsh")).unwrap();
+        let got =
+            resolve_source_file(repo, Some("subdir/my.zsh"), &PluginFilterConfig::default())
+                .unwrap();
         assert_eq!(got, hinted);
     }
 
@@ -115,24 +279,42 @@ mod tests {
         let f_plug = repo.join("a.plugin.zsh");
 
         fs::write(&f_theme, "# theme").unwrap();
-        let got1 = resolve_source_file(repo, None).unwrap();
+        let got1 = resolve_source_file(repo, None, &PluginFilterConfig::default()).unwrap();
         assert_eq!(got1, f_theme);
 
         fs::write(&f_zsh, "# zsh").unwrap();
-        let got2 = resolve_source_file(repo, None).unwrap();
+        let got2 = resolve_source_file(repo, None, &PluginFilterConfig::default()).unwrap();
         assert_eq!(got2, f_zsh);
 
         fs::write(&f_plug, "# plugin").unwrap();
-        let got3 = resolve_source_file(repo, None).unwrap();
+        let got3 = resolve_source_file(repo, None, &PluginFilterConfig::default()).unwrap();
         assert_eq!(got3, f_plug);
     }
 
+    #[test]
+    fn resolve_skips_zsh_files_excluded_by_filter() {
+        let tmp = tempdir().unwrap();
+        let repo = tmp.path();
+
+        fs::write(repo.join("compiled.zwc.zsh"), "# compiled").unwrap();
+        fs::write(repo.join("real.zsh"), "# real").unwrap();
+
+        let filter = PluginFilterConfig {
+            allowed_extensions: Vec::new(),
+            excluded_extensions: vec!["zsh".to_string()],
+        };
+        // With `.zsh` itself excluded, nothing matches — demonstrates the
+        // filter is actually consulted rather than bypassed for this path.
+        let err = resolve_source_file(repo, None, &filter).unwrap_err();
+        assert!(err.to_string().contains("no plugin file matched"));
+    }
+
     #[test]
     fn resolve_errors_when_nothing_matches() {
         let tmp = tempdir().unwrap();
         let repo = tmp.path();
 
-        let err = resolve_source_file(repo, None).unwrap_err();
+        let err = resolve_source_file(repo, None, &PluginFilterConfig::default()).unwrap_err();
         let msg = err.to_string();
         assert!(msg.contains("no plugin file matched"));
     }
@@ -0,0 +1,208 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A named snippet of zsh code used to render a plugin's compiled-init-script
+/// lines, referenced by name from [`crate::config::Plugin::apply`]. See
+/// [`builtin_templates`] and [`crate::config::Config::templates`].
+///
+/// A plain TOML string is shorthand for `{ value = "...", each = false }`:
+/// ```toml
+/// [templates]
+/// PATH = 'export PATH="{{ dir }}:$PATH"'
+/// defer = { value = 'zsh-defer source "{{ file }}"', each = true }
+/// ```
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum Template {
+    Value(String),
+    Full {
+        value: String,
+        #[serde(default)]
+        each: bool,
+    },
+}
+
+impl Template {
+    /// The raw template string, with `{{ dir }}`/`{{ file }}`/`{{ name }}`
+    /// placeholders not yet substituted.
+    fn value(&self) -> &str {
+        match self {
+            Template::Value(v) => v,
+            Template::Full { value, .. } => value,
+        }
+    }
+
+    /// Whether this template renders once per resolved source file (`true`)
+    /// or once per plugin (`false`, the default).
+    fn each(&self) -> bool {
+        match self {
+            Template::Value(_) => false,
+            Template::Full { each, .. } => *each,
+        }
+    }
+}
+
+/// The built-in templates every config starts with. A user `[templates]`
+/// entry with one of these names overrides it; any other name is added
+/// alongside them.
+pub fn builtin_templates() -> HashMap<String, Template> {
+    HashMap::from([
+        (
+            "source".to_string(),
+            Template::Full {
+                value: r#"source "{{ file }}""#.to_string(),
+                each: true,
+            },
+        ),
+        (
+            "PATH".to_string(),
+            Template::Value(r#"export PATH="{{ dir }}:$PATH""#.to_string()),
+        ),
+        (
+            "FPATH".to_string(),
+            Template::Value(r#"export FPATH="{{ dir }}:$FPATH""#.to_string()),
+        ),
+        (
+            "path".to_string(),
+            Template::Value(r#"path=( "{{ dir }}" $path )"#.to_string()),
+        ),
+        (
+            "fpath".to_string(),
+            Template::Value(r#"fpath=( "{{ dir }}" $fpath )"#.to_string()),
+        ),
+    ])
+}
+
+/// Merge a config's user-defined `[templates]` over the built-ins (see
+/// [`builtin_templates`]): same name overrides, anything else is added.
+pub fn effective_templates(user: &HashMap<String, Template>) -> HashMap<String, Template> {
+    let mut merged = builtin_templates();
+    merged.extend(user.iter().map(|(k, v)| (k.clone(), v.clone())));
+    merged
+}
+
+/// Substitute `{{ dir }}`, `{{ file }}`, and `{{ name }}` placeholders in a
+/// template string. A missing `file` renders as an empty string.
+fn render(tpl: &str, dir: &str, file: Option<&str>, name: &str) -> String {
+    tpl.replace("{{ dir }}", dir)
+        .replace("{{ file }}", file.unwrap_or(""))
+        .replace("{{ name }}", name)
+}
+
+/// Render every template named in `apply` for one plugin into zsh lines.
+///
+/// `files` is the plugin's resolved source file(s) — see
+/// [`crate::sync::resolve::resolve_source_files`]. A template with `each =
+/// true` renders once per entry in `files`; otherwise it renders once,
+/// using the first entry (if any) for `{{ file }}`.
+///
+/// A name in `apply` with no matching template is skipped, so a typo just
+/// drops that line rather than failing the whole render.
+pub fn render_plugin_lines(
+    apply: &[String],
+    templates: &HashMap<String, Template>,
+    dir: &Path,
+    files: &[PathBuf],
+    name: &str,
+) -> Vec<String> {
+    let dir_s = dir.to_string_lossy();
+    let mut lines = Vec::new();
+
+    for tpl_name in apply {
+        let Some(tpl) = templates.get(tpl_name) else {
+            continue;
+        };
+        if tpl.each() {
+            for f in files {
+                let file_s = f.to_string_lossy();
+                lines.push(render(tpl.value(), &dir_s, Some(&file_s), name));
+            }
+        } else {
+            let file_s = files.first().map(|f| f.to_string_lossy());
+            lines.push(render(tpl.value(), &dir_s, file_s.as_deref(), name));
+        }
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_substitutes_all_placeholders() {
+        let got = render(
+            r#"source "{{ file }}" # {{ name }} in {{ dir }}"#,
+            "/repo",
+            Some("/repo/a.zsh"),
+            "myplugin",
+        );
+        assert_eq!(got, r#"source "/repo/a.zsh" # myplugin in /repo"#);
+    }
+
+    #[test]
+    fn render_leaves_missing_file_blank() {
+        let got = render(r#"{{ file }}x"#, "/repo", None, "n");
+        assert_eq!(got, "x");
+    }
+
+    #[test]
+    fn effective_templates_user_entry_overrides_builtin() {
+        let mut user = HashMap::new();
+        user.insert(
+            "source".to_string(),
+            Template::Value("custom".to_string()),
+        );
+        let merged = effective_templates(&user);
+        assert_eq!(merged.get("source").unwrap().value(), "custom");
+        assert!(merged.contains_key("PATH"));
+    }
+
+    #[test]
+    fn render_plugin_lines_each_renders_once_per_file() {
+        let templates = builtin_templates();
+        let files = vec![PathBuf::from("/repo/a.zsh"), PathBuf::from("/repo/b.zsh")];
+        let lines = render_plugin_lines(
+            &["source".to_string()],
+            &templates,
+            Path::new("/repo"),
+            &files,
+            "n",
+        );
+        assert_eq!(
+            lines,
+            vec![
+                r#"source "/repo/a.zsh""#.to_string(),
+                r#"source "/repo/b.zsh""#.to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn render_plugin_lines_non_each_renders_once() {
+        let templates = builtin_templates();
+        let lines = render_plugin_lines(
+            &["fpath".to_string()],
+            &templates,
+            Path::new("/repo"),
+            &[],
+            "n",
+        );
+        assert_eq!(lines, vec![r#"fpath=( "/repo" $fpath )"#.to_string()]);
+    }
+
+    #[test]
+    fn render_plugin_lines_skips_unknown_template_names() {
+        let templates = builtin_templates();
+        let lines = render_plugin_lines(
+            &["nonexistent".to_string()],
+            &templates,
+            Path::new("/repo"),
+            &[],
+            "n",
+        );
+        assert!(lines.is_empty());
+    }
+}
@@ -1,10 +1,13 @@
-use anyhow::{Result, anyhow};
+use anyhow::{Result, anyhow, bail};
+use bzip2::read::BzDecoder;
 use flate2::read::GzDecoder;
 use sha2::{Digest, Sha256};
 use std::fs;
 use std::io::Read;
 use std::path::Path;
 use tempfile::NamedTempFile;
+use xz2::read::XzDecoder;
+use zstd::stream::read::Decoder as ZstdDecoder;
 
 /// Compute the SHA-256 checksum of a file.
 ///
@@ -36,26 +39,74 @@ pub fn make_executable(p: &Path) -> Result<()> {
     Ok(())
 }
 
-/// Extract the `rz` binary from a `.tar.gz` archive.
+/// Is `name` the `rz` binary, under either its Unix or Windows name?
+fn is_rz_binary_name(name: &str) -> bool {
+    name == "rz" || name == "rz.exe"
+}
+
+/// Reject an archive entry path that tries to escape the archive root (a
+/// "zip-slip" entry): absolute paths, and any path with a `..` component.
+///
+/// Every extractor below (tar and zip alike) checks each entry against this
+/// before it's even considered a candidate match, so a malicious archive
+/// entry named e.g. `../../rz` is refused outright rather than silently
+/// reduced to its basename.
+fn is_safe_entry_path(path: &Path) -> bool {
+    use std::path::Component;
+    path.components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// Extract the `rz`/`rz.exe` binary from a downloaded release asset.
+///
+/// Dispatches on `asset_name`'s extension: `.zip` archives are handled by
+/// [`extract_from_zip`]; `.tar.gz`/`.tgz`, `.tar.xz`, `.tar.bz2`, and
+/// `.tar.zst` are all handled by [`extract_from_tar`] behind the matching
+/// decompressor, selected by extension.
+///
+/// # Errors
+/// - If no `rz`/`rz.exe` binary is found in the archive, returns an error.
+/// - If the file cannot be opened, decompressed, or written, returns an error.
+/// - If an entry's path tries to escape the archive root, returns an error.
+pub fn extract_if_archive(temp_path: &Path, asset_name: &str) -> Result<NamedTempFile> {
+    if asset_name.ends_with(".zip") {
+        return extract_from_zip(temp_path);
+    }
+
+    let f = fs::File::open(temp_path)?;
+    if asset_name.ends_with(".tar.xz") {
+        extract_from_tar(tar::Archive::new(XzDecoder::new(f)))
+    } else if asset_name.ends_with(".tar.bz2") {
+        extract_from_tar(tar::Archive::new(BzDecoder::new(f)))
+    } else if asset_name.ends_with(".tar.zst") {
+        extract_from_tar(tar::Archive::new(ZstdDecoder::new(f)?))
+    } else {
+        extract_from_tar(tar::Archive::new(GzDecoder::new(f)))
+    }
+}
+
+/// Extract the `rz` binary from an already-opened tar archive, regardless of
+/// its underlying compression.
 ///
-/// - Opens the provided `temp_path` as a gzip-compressed tar archive.
-/// - Iterates over all entries until a file named exactly `"rz"` is found.
+/// - Iterates over all entries until a file named `"rz"` or `"rz.exe"` is
+///   found, rejecting any entry whose path escapes the archive root (see
+///   [`is_safe_entry_path`]).
 /// - Copies that entry into a new `NamedTempFile` with a `rz-` prefix.
 /// - Returns the temporary file containing the extracted binary.
 ///
 /// # Errors
 /// - If no `rz` binary is found in the archive, returns an error.
+/// - If an entry's path tries to escape the archive root, returns an error.
 /// - If the file cannot be opened, decompressed, or written, returns an error.
-pub fn extract_if_archive(temp_path: &Path) -> Result<NamedTempFile> {
-    let f = fs::File::open(temp_path)?;
-    let gz = GzDecoder::new(f);
-    let mut ar = tar::Archive::new(gz);
-
+fn extract_from_tar<R: Read>(mut ar: tar::Archive<R>) -> Result<NamedTempFile> {
     for entry in ar.entries()? {
         let mut e = entry?;
         let path = e.path()?;
+        if !is_safe_entry_path(&path) {
+            bail!("archive entry escapes archive root: {}", path.display());
+        }
         if let Some(name) = path.file_name().and_then(|s| s.to_str())
-            && name == "rz"
+            && is_rz_binary_name(name)
         {
             let mut tmp = tempfile::Builder::new().prefix("rz-").tempfile()?;
             std::io::copy(&mut e, tmp.as_file_mut())?;
@@ -66,6 +117,62 @@ pub fn extract_if_archive(temp_path: &Path) -> Result<NamedTempFile> {
     Err(anyhow!("archive does not contain rz binary"))
 }
 
+/// Extract the `rz`/`rz.exe` binary from a `.zip` archive.
+///
+/// - Opens the provided `temp_path` as a zip archive.
+/// - Iterates over all entries until a file named `"rz"` or `"rz.exe"` is found,
+///   rejecting any entry whose path escapes the archive root (see
+///   [`is_safe_entry_path`]).
+/// - Copies that entry into a new `NamedTempFile` with a `rz-` prefix.
+/// - Returns the temporary file containing the extracted binary.
+///
+/// # Errors
+/// - If no `rz`/`rz.exe` binary is found in the archive, returns an error.
+/// - If an entry's path tries to escape the archive root, returns an error.
+/// - If the file cannot be opened, read, or written, returns an error.
+fn extract_from_zip(temp_path: &Path) -> Result<NamedTempFile> {
+    let f = fs::File::open(temp_path)?;
+    let mut ar = zip::ZipArchive::new(f)?;
+
+    for i in 0..ar.len() {
+        let mut e = ar.by_index(i)?;
+        let entry_path = Path::new(e.name());
+        if !is_safe_entry_path(entry_path) {
+            bail!("archive entry escapes archive root: {}", entry_path.display());
+        }
+        let name = match entry_path.file_name().and_then(|s| s.to_str()) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        if is_rz_binary_name(&name) {
+            let mut tmp = tempfile::Builder::new().prefix("rz-").tempfile()?;
+            std::io::copy(&mut e, tmp.as_file_mut())?;
+            return Ok(tmp);
+        }
+    }
+
+    Err(anyhow!("archive does not contain rz binary"))
+}
+
+/// Verify a downloaded archive's SHA-256 digest against `expected_sha256`
+/// before extracting it, so self-update can gate on a published
+/// `SHA256SUMS` entry without trusting the archive contents first.
+///
+/// # Errors
+/// - Returns an error if the computed digest doesn't match `expected_sha256`.
+/// - Otherwise, propagates any error from [`extract_if_archive`].
+pub fn extract_verified(
+    temp_path: &Path,
+    asset_name: &str,
+    expected_sha256: &str,
+) -> Result<NamedTempFile> {
+    let actual = sha256_file(temp_path)?;
+    if !expected_sha256.eq_ignore_ascii_case(&actual) {
+        bail!("checksum mismatch for {asset_name}: expected {expected_sha256}, got {actual}");
+    }
+    extract_if_archive(temp_path, asset_name)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,10 +227,24 @@ mod tests {
         }
     }
 
+    fn make_zip_with_single_file(name: &str, contents: &[u8]) -> NamedTempFile {
+        use zip::write::SimpleFileOptions;
+
+        let tmp = tempfile::NamedTempFile::new().expect("zip temp");
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(tmp.path()).expect("open zip out"));
+        writer
+            .start_file(name, SimpleFileOptions::default())
+            .expect("start zip entry");
+        writer.write_all(contents).expect("write zip entry");
+        writer.finish().expect("finish zip");
+
+        tmp
+    }
+
     #[test]
-    fn extract_if_archive_finds_rz_and_returns_tempfile() {
+    fn extract_if_archive_finds_rz_in_targz() {
         let tgz = make_targz_with_single_file("rz", b"dummy-binary");
-        let extracted = extract_if_archive(tgz.path()).expect("extract ok");
+        let extracted = extract_if_archive(tgz.path(), "rz-v0.1.2-linux-x86_64.tar.gz").expect("extract ok");
 
         let mut buf = Vec::new();
         std::fs::File::open(extracted.path())
@@ -134,13 +255,181 @@ mod tests {
     }
 
     #[test]
-    fn extract_if_archive_errors_when_rz_not_present() {
+    fn extract_if_archive_errors_when_rz_not_present_in_targz() {
         let tgz = make_targz_with_single_file("foo", b"not rz");
-        let err = extract_if_archive(tgz.path()).unwrap_err();
+        let err = extract_if_archive(tgz.path(), "rz-v0.1.2-linux-x86_64.tar.gz").unwrap_err();
         let msg = format!("{err:#}");
         assert!(
             msg.contains("archive does not contain rz binary"),
             "unexpected error: {msg}"
         );
     }
+
+    #[test]
+    fn extract_if_archive_finds_rz_exe_in_zip() {
+        let zip = make_zip_with_single_file("rz.exe", b"dummy-exe-binary");
+        let extracted = extract_if_archive(zip.path(), "rz-v0.1.2-windows-x86_64.zip").expect("extract ok");
+
+        let mut buf = Vec::new();
+        std::fs::File::open(extracted.path())
+            .unwrap()
+            .read_to_end(&mut buf)
+            .unwrap();
+        assert_eq!(buf, b"dummy-exe-binary");
+    }
+
+    #[test]
+    fn extract_if_archive_errors_when_rz_not_present_in_zip() {
+        let zip = make_zip_with_single_file("foo", b"not rz");
+        let err = extract_if_archive(zip.path(), "rz-v0.1.2-windows-x86_64.zip").unwrap_err();
+        let msg = format!("{err:#}");
+        assert!(
+            msg.contains("archive does not contain rz binary"),
+            "unexpected error: {msg}"
+        );
+    }
+
+    fn make_tar_with_single_file<W: Write>(name: &str, contents: &[u8], out: W) -> W {
+        let mut tar = tar::Builder::new(out);
+        let mut payload = tempfile::NamedTempFile::new().expect("payload temp");
+        payload.write_all(contents).expect("write payload");
+        tar.append_path_with_name(payload.path(), name)
+            .expect("append to tar");
+        tar.into_inner().expect("finish tar")
+    }
+
+    #[test]
+    fn extract_if_archive_finds_rz_in_tar_xz() {
+        use xz2::write::XzEncoder;
+
+        let tmp = tempfile::NamedTempFile::new().expect("tar.xz temp");
+        let xz = XzEncoder::new(std::fs::File::create(tmp.path()).expect("open xz out"), 6);
+        let xz = make_tar_with_single_file("rz", b"dummy-xz-binary", xz);
+        xz.finish().expect("finish xz");
+
+        let extracted =
+            extract_if_archive(tmp.path(), "rz-v0.1.2-linux-x86_64.tar.xz").expect("extract ok");
+        let mut buf = Vec::new();
+        std::fs::File::open(extracted.path())
+            .unwrap()
+            .read_to_end(&mut buf)
+            .unwrap();
+        assert_eq!(buf, b"dummy-xz-binary");
+    }
+
+    #[test]
+    fn extract_if_archive_finds_rz_in_tar_bz2() {
+        use bzip2::Compression;
+        use bzip2::write::BzEncoder;
+
+        let tmp = tempfile::NamedTempFile::new().expect("tar.bz2 temp");
+        let bz = BzEncoder::new(
+            std::fs::File::create(tmp.path()).expect("open bz2 out"),
+            Compression::default(),
+        );
+        let bz = make_tar_with_single_file("rz", b"dummy-bz2-binary", bz);
+        bz.finish().expect("finish bz2");
+
+        let extracted =
+            extract_if_archive(tmp.path(), "rz-v0.1.2-linux-x86_64.tar.bz2").expect("extract ok");
+        let mut buf = Vec::new();
+        std::fs::File::open(extracted.path())
+            .unwrap()
+            .read_to_end(&mut buf)
+            .unwrap();
+        assert_eq!(buf, b"dummy-bz2-binary");
+    }
+
+    #[test]
+    fn extract_if_archive_finds_rz_in_tar_zst() {
+        use zstd::stream::write::Encoder as ZstdEncoder;
+
+        let tmp = tempfile::NamedTempFile::new().expect("tar.zst temp");
+        let zst = ZstdEncoder::new(std::fs::File::create(tmp.path()).expect("open zst out"), 0)
+            .expect("zstd encoder");
+        let zst = make_tar_with_single_file("rz", b"dummy-zst-binary", zst);
+        zst.finish().expect("finish zst");
+
+        let extracted =
+            extract_if_archive(tmp.path(), "rz-v0.1.2-linux-x86_64.tar.zst").expect("extract ok");
+        let mut buf = Vec::new();
+        std::fs::File::open(extracted.path())
+            .unwrap()
+            .read_to_end(&mut buf)
+            .unwrap();
+        assert_eq!(buf, b"dummy-zst-binary");
+    }
+
+    #[test]
+    fn extract_if_archive_rejects_path_traversal_in_targz() {
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+
+        // `tar::Builder` refuses to write a `..` path through `set_path`, so
+        // the malicious entry name goes straight into the header bytes.
+        let tgz = tempfile::NamedTempFile::new().expect("targz temp");
+        let gz = GzEncoder::new(
+            std::fs::File::create(tgz.path()).expect("open gz out"),
+            Compression::default(),
+        );
+        let mut tar = tar::Builder::new(gz);
+        let payload: &[u8] = b"malicious";
+        let mut header = tar::Header::new_old();
+        header.as_old_mut().name[..8].copy_from_slice(b"../../rz");
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_size(payload.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append(&header, payload).expect("append to tar");
+        tar.into_inner()
+            .expect("finish tar")
+            .finish()
+            .expect("finish gz");
+
+        let err = extract_if_archive(tgz.path(), "rz-v0.1.2-linux-x86_64.tar.gz").unwrap_err();
+        let msg = format!("{err:#}");
+        assert!(
+            msg.contains("escapes archive root"),
+            "unexpected error: {msg}"
+        );
+    }
+
+    #[test]
+    fn extract_if_archive_rejects_path_traversal_in_zip() {
+        let zip = make_zip_with_single_file("../../rz", b"malicious");
+        let err = extract_if_archive(zip.path(), "rz-v0.1.2-windows-x86_64.zip").unwrap_err();
+        let msg = format!("{err:#}");
+        assert!(
+            msg.contains("escapes archive root"),
+            "unexpected error: {msg}"
+        );
+    }
+
+    #[test]
+    fn extract_verified_rejects_checksum_mismatch() {
+        let tgz = make_targz_with_single_file("rz", b"dummy-binary");
+        let err = extract_verified(
+            tgz.path(),
+            "rz-v0.1.2-linux-x86_64.tar.gz",
+            "0000000000000000000000000000000000000000000000000000000000000",
+        )
+        .unwrap_err();
+        let msg = format!("{err:#}");
+        assert!(msg.contains("checksum mismatch"), "unexpected error: {msg}");
+    }
+
+    #[test]
+    fn extract_verified_extracts_on_checksum_match() {
+        let tgz = make_targz_with_single_file("rz", b"dummy-binary");
+        let expected = sha256_file(tgz.path()).unwrap();
+        let extracted =
+            extract_verified(tgz.path(), "rz-v0.1.2-linux-x86_64.tar.gz", &expected).expect("extract ok");
+
+        let mut buf = Vec::new();
+        std::fs::File::open(extracted.path())
+            .unwrap()
+            .read_to_end(&mut buf)
+            .unwrap();
+        assert_eq!(buf, b"dummy-binary");
+    }
 }
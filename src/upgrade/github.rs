@@ -1,84 +1,103 @@
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
+use indicatif::ProgressBar;
+use reqwest::StatusCode;
 use reqwest::blocking::Client;
 use reqwest::blocking::Response;
-use reqwest::header::{ACCEPT, HeaderMap, HeaderValue, USER_AGENT};
-use serde::Deserialize;
+use reqwest::header::{ACCEPT, ACCEPT_RANGES, CONTENT_LENGTH, HeaderMap, HeaderValue, RANGE, USER_AGENT};
 use std::env;
-use tempfile::NamedTempFile;
-
-/// Representation of a GitHub release response.
-/// Contains the tag name and associated release assets.
-#[derive(Debug, Deserialize)]
-pub struct Release {
-    pub tag_name: String,
-    pub assets: Vec<Asset>,
-}
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::sync::progress::download_bar_style;
+use crate::upgrade::release::{Release, ReleaseSource};
 
-/// Representation of a single GitHub release asset.
-/// Includes the filename and the download URL.
-#[derive(Debug, Deserialize)]
-pub struct Asset {
-    pub name: String,
-    pub browser_download_url: String,
+/// Release source for a GitHub-hosted repo (`<owner>/<repo>`).
+pub struct GitHubSource {
+    pub repo: String,
 }
 
-/// Create a GitHub API client with default headers.
-///
-/// - Adds `Accept` and `User-Agent` headers (required by GitHub API).
-/// - If `GITHUB_TOKEN` is set in the environment, adds an Authorization header.
-///
-/// # Errors
-/// - Returns an error if the client cannot be built.
-/// - Returns an error if the token is invalid for the header.
-pub fn gh_client() -> Result<Client> {
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        ACCEPT,
-        HeaderValue::from_static("application/vnd.github+json"),
-    );
-    headers.insert(
-        USER_AGENT,
-        HeaderValue::from_static("gotokazuki-rz-upgrader"),
-    );
-    if let Ok(tok) = env::var("GITHUB_TOKEN") {
+impl ReleaseSource for GitHubSource {
+    /// Build a GitHub API client with default headers.
+    ///
+    /// - Adds `Accept` and `User-Agent` headers (required by GitHub API).
+    /// - If `GITHUB_TOKEN` is set in the environment, adds an Authorization header.
+    ///
+    /// # Errors
+    /// - Returns an error if the client cannot be built.
+    /// - Returns an error if the token is invalid for the header.
+    fn client(&self) -> Result<Client> {
+        let mut headers = HeaderMap::new();
         headers.insert(
-            "Authorization",
-            HeaderValue::from_str(&format!("Bearer {}", tok))?,
+            ACCEPT,
+            HeaderValue::from_static("application/vnd.github+json"),
         );
+        headers.insert(
+            USER_AGENT,
+            HeaderValue::from_static("gotokazuki-rz-upgrader"),
+        );
+        if let Ok(tok) = env::var("GITHUB_TOKEN") {
+            headers.insert(
+                "Authorization",
+                HeaderValue::from_str(&format!("Bearer {}", tok))?,
+            );
+        }
+        let client = Client::builder().default_headers(headers).build()?;
+        Ok(client)
     }
-    let client = Client::builder().default_headers(headers).build()?;
-    Ok(client)
-}
 
-/// Fetch metadata for the latest release from GitHub.
-///
-/// Uses the `/releases/latest` API endpoint to retrieve the release tag
-/// and asset list.
-///
-/// # Errors
-/// - Returns an error if the request fails or the response cannot be parsed.
-pub fn fetch_latest_release(client: &Client) -> Result<Release> {
-    let url = "https://api.github.com/repos/gotokazuki/rat-zsh/releases/latest";
-    let rel: Release = client.get(url).send()?.error_for_status()?.json()?;
-    Ok(rel)
+    /// Fetch metadata for the latest release via the `/releases/latest` API
+    /// endpoint.
+    ///
+    /// # Errors
+    /// - Returns an error if the request fails or the response cannot be parsed.
+    fn fetch_latest(&self, client: &Client) -> Result<Release> {
+        let url = format!(
+            "https://api.github.com/repos/{}/releases/latest",
+            self.repo
+        );
+        let rel: Release = client.get(url).send()?.error_for_status()?.json()?;
+        Ok(rel)
+    }
 }
 
 /// Generate candidate asset filenames for the current platform.
 ///
-/// The naming convention is assumed to be:
-/// `rz-<tag>-<os>-<arch>.tar.gz`
+/// The naming convention is assumed to be `rz-<tag>-<os>-<arch>.<ext>`,
+/// with `<ext>` being both `tar.gz` and `zip` (mirrors/forks may publish
+/// either), preferring the platform's native archive format. Windows
+/// additionally tries the Rust target-triple-style `<arch>-pc-windows-msvc`
+/// naming some release pipelines use.
 ///
 /// # Errors
 /// - Returns an error if the current OS/arch is unsupported.
 pub fn candidate_asset_names(tag: &str) -> Result<Vec<String>> {
     let (os, arch) = detect_target()?;
-    Ok(vec![format!("rz-{}-{}-{}.tar.gz", tag, os, arch)])
+
+    let mut stems = vec![format!("rz-{tag}-{os}-{arch}")];
+    if os == "windows" {
+        stems.push(format!("rz-{tag}-{arch}-pc-windows-msvc"));
+    }
+
+    let exts: [&str; 2] = if os == "windows" {
+        ["zip", "tar.gz"]
+    } else {
+        ["tar.gz", "zip"]
+    };
+
+    let mut names = Vec::with_capacity(stems.len() * exts.len());
+    for stem in &stems {
+        for ext in exts {
+            names.push(format!("{stem}.{ext}"));
+        }
+    }
+    Ok(names)
 }
 
 /// Detect the current OS and architecture using Rustâ€™s compile-time constants.
 ///
 /// # Returns
-/// - `"linux"` or `"macos"`
+/// - `"linux"`, `"macos"`, or `"windows"`
 /// - `"x86_64"` or `"aarch64"`
 ///
 /// # Errors
@@ -87,6 +106,7 @@ fn detect_target() -> Result<(&'static str, &'static str)> {
     let os = match std::env::consts::OS {
         "linux" => "linux",
         "macos" => "macos",
+        "windows" => "windows",
         other => bail!("unsupported OS: {}", other),
     };
     let arch = match std::env::consts::ARCH {
@@ -97,32 +117,110 @@ fn detect_target() -> Result<(&'static str, &'static str)> {
     Ok((os, arch))
 }
 
-/// Download a file from GitHub to a temporary file.
+/// Check whether the server advertises byte-range support for `url` via a
+/// `HEAD` request's `Accept-Ranges: bytes` header. Any failure (network
+/// error, missing/other header value) is treated as "no".
+fn server_supports_ranges(client: &Client, url: &str) -> bool {
+    client
+        .head(url)
+        .send()
+        .ok()
+        .and_then(|resp| resp.headers().get(ACCEPT_RANGES)?.to_str().ok().map(str::to_string))
+        .is_some_and(|v| v.eq_ignore_ascii_case("bytes"))
+}
+
+/// Download a file from `url` to `dest`, showing a byte-progress bar sized
+/// from the response's `Content-Length`, with transfer rate and ETA.
 ///
-/// The temporary file will have a `.tar.gz` suffix so it can be
-/// properly identified and handled later.
+/// The transfer is written to a `<dest>.part` sibling file first, then
+/// renamed into place on success. If `<dest>.part` already exists from a
+/// previous attempt and the server advertises `Accept-Ranges: bytes`, the
+/// download resumes with a `Range: bytes=<len>-` request; if the server
+/// doesn't honor it (responds `200` instead of `206`), the partial file is
+/// discarded and the download restarts from scratch.
 ///
 /// # Errors
 /// - Returns an error if the request fails.
-/// - Returns an error if writing to the temporary file fails.
-pub fn download_to_temp(client: &Client, url: &str) -> Result<NamedTempFile> {
-    let mut resp: Response = client.get(url).send()?.error_for_status()?;
-    let tmp = tempfile::Builder::new().suffix(".tar.gz").tempfile()?;
-    std::io::copy(&mut resp, &mut tmp.as_file())?;
-    Ok(tmp)
+/// - Returns an error if writing to the destination file fails.
+pub fn download_to_temp(client: &Client, url: &str, dest: &Path) -> Result<PathBuf> {
+    let part_path = sibling_with_suffix(dest, ".part");
+    let existing_len = fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
+    let resume = existing_len > 0 && server_supports_ranges(client, url);
+
+    let mut req = client.get(url);
+    if resume {
+        req = req.header(RANGE, format!("bytes={existing_len}-"));
+    }
+    let mut resp: Response = req.send()?.error_for_status()?;
+
+    let resuming = resume && resp.status() == StatusCode::PARTIAL_CONTENT;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resuming)
+        .truncate(!resuming)
+        .open(&part_path)
+        .with_context(|| format!("failed to open {}", part_path.display()))?;
+
+    let already = if resuming { existing_len } else { 0 };
+    let remaining = resp
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+
+    let pb = ProgressBar::new(remaining.map(|r| already + r).unwrap_or(0));
+    pb.set_style(download_bar_style());
+    pb.set_position(already);
+    pb.set_message(format!(
+        "downloading {}",
+        dest.file_name().and_then(|n| n.to_str()).unwrap_or(url)
+    ));
+
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = resp.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        file.write_all(&buf[..n])?;
+        pb.inc(n as u64);
+    }
+    pb.finish_and_clear();
+
+    fs::rename(&part_path, dest).with_context(|| format!("failed to finalize {}", dest.display()))?;
+    Ok(dest.to_path_buf())
+}
+
+/// Append `suffix` to `path`'s file name, e.g. `foo.tar.gz` + `.part` →
+/// `foo.tar.gz.part`.
+fn sibling_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use httpmock::Method;
     use httpmock::prelude::*;
     use std::fs;
 
+    fn test_client() -> Client {
+        GitHubSource {
+            repo: "gotokazuki/rat-zsh".to_string(),
+        }
+        .client()
+        .expect("client")
+    }
+
     #[test]
-    fn candidate_asset_names_formats_expected_name() {
+    fn candidate_asset_names_formats_expected_names() {
         let os = match std::env::consts::OS {
             "linux" => "linux",
             "macos" => "macos",
+            "windows" => "windows",
             other => {
                 eprintln!("unsupported test os: {other}");
                 return;
@@ -139,53 +237,101 @@ mod tests {
 
         let tag = "v0.1.2";
         let got = candidate_asset_names(tag).expect("ok");
-        assert_eq!(got, vec![format!("rz-{tag}-{os}-{arch}.tar.gz")]);
-    }
-
-    #[test]
-    fn release_struct_deserializes_from_github_like_json() {
-        let json = r#"
-{
-    "tag_name": "v1.2.3",
-    "assets": [
-        {
-            "name": "rz-v1.2.3-macos-aarch64.tar.gz",
-            "browser_download_url": "https://example.com/rz-v1.2.3-macos-aarch64.tar.gz"
+        assert!(got.contains(&format!("rz-{tag}-{os}-{arch}.tar.gz")));
+        assert!(got.contains(&format!("rz-{tag}-{os}-{arch}.zip")));
+        if os == "windows" {
+            assert!(got.contains(&format!("rz-{tag}-{arch}-pc-windows-msvc.zip")));
+            assert_eq!(got[0], format!("rz-{tag}-{os}-{arch}.zip"));
+        } else {
+            assert_eq!(got[0], format!("rz-{tag}-{os}-{arch}.tar.gz"));
         }
-    ]
-}"#;
-
-        let rel: Release = serde_json::from_str(json).expect("deserialize");
-        assert_eq!(rel.tag_name, "v1.2.3");
-        assert_eq!(rel.assets.len(), 1);
-        assert_eq!(rel.assets[0].name, "rz-v1.2.3-macos-aarch64.tar.gz");
     }
 
     #[test]
-    fn download_to_temp_writes_body_and_uses_tar_gz_suffix() {
+    fn download_to_temp_writes_body_to_dest() {
         let server = MockServer::start();
         let body = b"hello world";
         let m = server.mock(|when, then| {
             when.method(GET).path("/file.tar.gz");
             then.status(200)
                 .header("Content-Type", "application/octet-stream")
+                .header("Content-Length", body.len().to_string())
                 .body(body as &[_]);
         });
 
-        let client = gh_client().expect("client");
+        let client = test_client();
         let url = format!("{}/file.tar.gz", server.base_url());
-        let tmp = download_to_temp(&client, &url).expect("download");
-
-        let name = tmp
-            .path()
-            .file_name()
-            .and_then(|s| s.to_str())
-            .unwrap_or("");
-        assert!(name.ends_with(".tar.gz"), "actual name: {name}");
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let dest = tmp_dir.path().join("file.tar.gz");
+        let got = download_to_temp(&client, &url, &dest).expect("download");
 
-        let saved = fs::read(tmp.path()).expect("read");
+        assert_eq!(got, dest);
+        assert!(!sibling_with_suffix(&dest, ".part").exists());
+        let saved = fs::read(&dest).expect("read");
         assert_eq!(saved, body);
 
         m.assert();
     }
+
+    #[test]
+    fn download_to_temp_resumes_partial_download_when_server_supports_ranges() {
+        let server = MockServer::start();
+        let full_body = b"hello world";
+        let already = &full_body[..5];
+        let rest = &full_body[5..];
+
+        let head_mock = server.mock(|when, then| {
+            when.method(Method::HEAD).path("/file.tar.gz");
+            then.status(200).header("Accept-Ranges", "bytes");
+        });
+        let range_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/file.tar.gz")
+                .header("Range", "bytes=5-");
+            then.status(206)
+                .header("Content-Length", rest.len().to_string())
+                .body(rest);
+        });
+
+        let client = test_client();
+        let url = format!("{}/file.tar.gz", server.base_url());
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let dest = tmp_dir.path().join("file.tar.gz");
+        fs::write(sibling_with_suffix(&dest, ".part"), already).unwrap();
+
+        let got = download_to_temp(&client, &url, &dest).expect("download");
+        assert_eq!(fs::read(got).expect("read"), full_body);
+
+        head_mock.assert();
+        range_mock.assert();
+    }
+
+    #[test]
+    fn download_to_temp_restarts_when_server_ignores_range() {
+        let server = MockServer::start();
+        let full_body = b"hello world";
+
+        let head_mock = server.mock(|when, then| {
+            when.method(Method::HEAD).path("/file.tar.gz");
+            then.status(200).header("Accept-Ranges", "bytes");
+        });
+        let get_mock = server.mock(|when, then| {
+            when.method(GET).path("/file.tar.gz");
+            then.status(200)
+                .header("Content-Length", full_body.len().to_string())
+                .body(full_body as &[_]);
+        });
+
+        let client = test_client();
+        let url = format!("{}/file.tar.gz", server.base_url());
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let dest = tmp_dir.path().join("file.tar.gz");
+        fs::write(sibling_with_suffix(&dest, ".part"), b"stale partial").unwrap();
+
+        let got = download_to_temp(&client, &url, &dest).expect("download");
+        assert_eq!(fs::read(got).expect("read"), full_body);
+
+        head_mock.assert();
+        get_mock.assert();
+    }
 }
@@ -0,0 +1,133 @@
+use anyhow::Result;
+use reqwest::blocking::Client;
+use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
+use serde::Deserialize;
+use std::env;
+
+use crate::upgrade::release::{Asset, Release, ReleaseSource};
+
+/// Release source for a GitLab-hosted project (`<group>/<project>`, possibly
+/// with nested subgroups).
+pub struct GitLabSource {
+    pub project: String,
+}
+
+impl ReleaseSource for GitLabSource {
+    /// Build a GitLab API client with default headers.
+    ///
+    /// - Adds a `User-Agent` header.
+    /// - If `GITLAB_TOKEN` is set in the environment, adds a `PRIVATE-TOKEN`
+    ///   header.
+    ///
+    /// # Errors
+    /// - Returns an error if the client cannot be built.
+    /// - Returns an error if the token is invalid for the header.
+    fn client(&self) -> Result<Client> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            USER_AGENT,
+            HeaderValue::from_static("gotokazuki-rz-upgrader"),
+        );
+        if let Ok(tok) = env::var("GITLAB_TOKEN") {
+            headers.insert("PRIVATE-TOKEN", HeaderValue::from_str(&tok)?);
+        }
+        let client = Client::builder().default_headers(headers).build()?;
+        Ok(client)
+    }
+
+    /// Fetch metadata for the latest release via the `releases/permalink/latest`
+    /// API endpoint, translating GitLab's `assets.links[]` shape into the
+    /// common [`Release`]/[`Asset`] representation.
+    ///
+    /// # Errors
+    /// - Returns an error if the request fails or the response cannot be parsed.
+    fn fetch_latest(&self, client: &Client) -> Result<Release> {
+        let url = format!(
+            "https://gitlab.com/api/v4/projects/{}/releases/permalink/latest",
+            urlencode(&self.project)
+        );
+        let rel: GitLabRelease = client.get(url).send()?.error_for_status()?.json()?;
+        Ok(rel.into())
+    }
+}
+
+/// URL-encode a GitLab project path for use as a `:id` path segment (GitLab
+/// accepts either the numeric project ID or the URL-encoded `namespace/path`).
+fn urlencode(project: &str) -> String {
+    let mut out = String::with_capacity(project.len());
+    for b in project.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+/// GitLab's native release JSON shape, as returned by the `releases` API.
+#[derive(Debug, Deserialize)]
+struct GitLabRelease {
+    tag_name: String,
+    assets: GitLabAssets,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabAssets {
+    links: Vec<GitLabLink>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabLink {
+    name: String,
+    url: String,
+}
+
+impl From<GitLabRelease> for Release {
+    fn from(rel: GitLabRelease) -> Self {
+        Release {
+            tag_name: rel.tag_name,
+            assets: rel
+                .assets
+                .links
+                .into_iter()
+                .map(|l| Asset {
+                    name: l.name,
+                    browser_download_url: l.url,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urlencode_escapes_slash_in_project_path() {
+        assert_eq!(urlencode("group/subgroup/project"), "group%2Fsubgroup%2Fproject");
+    }
+
+    #[test]
+    fn gitlab_release_converts_links_to_assets() {
+        let json = r#"
+{
+    "tag_name": "v1.2.3",
+    "assets": {
+        "links": [
+            {
+                "name": "rz-v1.2.3-linux-x86_64.tar.gz",
+                "url": "https://example.com/rz-v1.2.3-linux-x86_64.tar.gz"
+            }
+        ]
+    }
+}"#;
+        let gl: GitLabRelease = serde_json::from_str(json).expect("deserialize");
+        let rel: Release = gl.into();
+        assert_eq!(rel.tag_name, "v1.2.3");
+        assert_eq!(rel.assets.len(), 1);
+        assert_eq!(rel.assets[0].name, "rz-v1.2.3-linux-x86_64.tar.gz");
+    }
+}
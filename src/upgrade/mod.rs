@@ -1,18 +1,53 @@
 mod archive;
 mod github;
+mod gitlab;
+mod release;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use indicatif::ProgressBar;
 use std::path::Path;
 use std::{fs, time::Duration};
 
-use crate::progress::ok_style;
-use crate::{paths::paths, progress::spinner_style};
-use archive::{extract_if_archive, make_executable, sha256_file};
-use github::{candidate_asset_names, download_to_temp, fetch_latest_release, gh_client};
+use crate::config::{UpgradeConfig, load_config};
+use crate::paths::paths;
+use crate::sync::progress::{ok_style, spinner_style};
+use archive::{extract_if_archive, extract_verified, make_executable, sha256_file};
+use github::{GitHubSource, candidate_asset_names, download_to_temp};
+use gitlab::GitLabSource;
+use release::{ReleaseSource, find_checksum_asset, parse_checksum_for_file};
+
+/// Compute a file's SHA-256 digest. Shared with [`crate::lock`], which also
+/// needs integrity digests but for synced plugin source files rather than
+/// downloaded release assets.
+pub(crate) use archive::sha256_file as lock_sha256_file;
+
+const DEFAULT_UPSTREAM_REPO: &str = "gotokazuki/rat-zsh";
+
+/// Build the [`ReleaseSource`] selected by `cfg`.
+///
+/// `cfg.source` of `"gitlab"` selects [`GitLabSource`]; anything else
+/// (including empty, the default) selects [`GitHubSource`]. An empty
+/// `cfg.repo` falls back to the upstream `gotokazuki/rat-zsh` repo.
+fn release_source(cfg: &UpgradeConfig) -> Box<dyn ReleaseSource> {
+    let repo = if cfg.repo.is_empty() {
+        DEFAULT_UPSTREAM_REPO.to_string()
+    } else {
+        cfg.repo.clone()
+    };
+    match cfg.source.as_str() {
+        "gitlab" => Box::new(GitLabSource { project: repo }),
+        _ => Box::new(GitHubSource { repo }),
+    }
+}
 
 /// Resolve the target binary path inside `bin_dir`.
-/// Always returns `<bin_dir>/rz`.
+/// Returns `<bin_dir>/rz.exe` on Windows, `<bin_dir>/rz` elsewhere.
+#[cfg(windows)]
+fn target_bin_path(bin_dir: &Path) -> std::path::PathBuf {
+    bin_dir.join("rz.exe")
+}
+
+#[cfg(not(windows))]
 fn target_bin_path(bin_dir: &Path) -> std::path::PathBuf {
     bin_dir.join("rz")
 }
@@ -23,23 +58,34 @@ enum ReplaceOutcome {
     Unchanged,
 }
 
-/// Upgrade the `rz` binary to the latest GitHub release.
+/// Upgrade the `rz` binary to the latest release.
 ///
 /// Process:
-/// 1. Create `~/.rz/bin` directory if missing.
-/// 2. Fetch the latest release metadata from GitHub API.
+/// 1. Create the `bin` and `cache` directories if missing.
+/// 2. Fetch the latest release metadata from the configured release source
+///    (see [`crate::config::UpgradeConfig`]; defaults to the upstream
+///    GitHub repo).
 /// 3. Compare release tag with current `CARGO_PKG_VERSION`.
 ///    - If equal → print "already up to date" and exit.
 /// 4. Find the matching release asset for this OS/arch.
 ///    - Uses `candidate_asset_names` to build expected names.
 ///    - Falls back to first asset if no exact match.
-/// 5. Download the asset tarball.
-/// 6. Extract the `rz` binary from archive.
-/// 7. Atomically replace the old binary with the new one
+/// 5. Download the asset archive into the cache directory.
+/// 6. Look up its SHA-256 in a companion checksums asset, if the release
+///    publishes one (see [`release::find_checksum_asset`]). When
+///    `require_checksum` is `true`, a missing checksum asset is a hard
+///    failure instead of a warning.
+/// 7. Extract the `rz` binary from the archive (`.tar.gz`/`.tgz`, `.tar.xz`,
+///    `.tar.bz2`, `.tar.zst`, or `.zip` — see [`archive::extract_if_archive`]).
+///    When a checksum was found in step 6, this goes through
+///    [`archive::extract_verified`] instead, which refuses to extract an
+///    archive whose digest doesn't match.
+/// 8. Atomically replace the old binary with the new one
 ///    (skip replacement if SHA-256 hash is unchanged).
-pub fn cmd_upgrade() -> Result<()> {
+pub fn cmd_upgrade(require_checksum: bool) -> Result<()> {
     let p = paths()?;
     fs::create_dir_all(&p.bin)?;
+    fs::create_dir_all(&p.cache)?;
     let target_bin = target_bin_path(&p.bin);
 
     let pb = ProgressBar::new_spinner();
@@ -47,8 +93,10 @@ pub fn cmd_upgrade() -> Result<()> {
     pb.enable_steady_tick(Duration::from_millis(200));
     pb.set_message("resolving latest release…");
 
-    let client = gh_client()?;
-    let rel = fetch_latest_release(&client)?;
+    let upgrade_cfg = load_config().map(|c| c.upgrade).unwrap_or_default();
+    let source = release_source(&upgrade_cfg);
+    let client = source.client()?;
+    let rel = source.fetch_latest(&client)?;
 
     let latest_version = rel.tag_name.trim_start_matches('v');
     let current_version = env!("CARGO_PKG_VERSION");
@@ -71,12 +119,52 @@ pub fn cmd_upgrade() -> Result<()> {
         rel.assets.first().context("no assets in latest release")?
     };
 
-    pb.set_message(format!("downloading {}", asset.name));
-    let downloaded = download_to_temp(&client, &asset.browser_download_url)
+    let asset_dest = p.cache.join(&asset.name);
+    let downloaded = download_to_temp(&client, &asset.browser_download_url, &asset_dest)
         .with_context(|| format!("failed to download: {}", asset.browser_download_url))?;
 
+    let expected_sha256 = match find_checksum_asset(&rel, &asset.name, tag) {
+        Some(sums_asset) => {
+            let sums_dest = p.cache.join(&sums_asset.name);
+            let sums_file = download_to_temp(&client, &sums_asset.browser_download_url, &sums_dest)
+                .with_context(|| format!("failed to download: {}", sums_asset.browser_download_url))?;
+            let sums_text = fs::read_to_string(&sums_file)
+                .with_context(|| format!("failed to read checksum file: {}", sums_asset.name))?;
+            let _ = fs::remove_file(&sums_file);
+            let expected = parse_checksum_for_file(&sums_text, &asset.name).with_context(|| {
+                format!("no checksum entry for {} in {}", asset.name, sums_asset.name)
+            })?;
+            Some(expected)
+        }
+        None if require_checksum => {
+            let _ = fs::remove_file(&downloaded);
+            bail!(
+                "no checksum asset published for {} (retry without --require-checksum to skip verification)",
+                asset.name
+            );
+        }
+        None => {
+            eprintln!(
+                "warning: no checksum asset found for {}; skipping verification",
+                asset.name
+            );
+            None
+        }
+    };
+
     pb.set_message("extracting package…");
-    let extracted = extract_if_archive(downloaded.path())?;
+    let extraction = match &expected_sha256 {
+        Some(expected) => extract_verified(&downloaded, &asset.name, expected),
+        None => extract_if_archive(&downloaded, &asset.name),
+    };
+    let extracted = match extraction {
+        Ok(extracted) => extracted,
+        Err(e) => {
+            let _ = fs::remove_file(&downloaded);
+            return Err(e);
+        }
+    };
+    let _ = fs::remove_file(&downloaded);
 
     pb.set_message("installing rz…");
     match atomic_replace(extracted.path(), &target_bin)? {
@@ -93,15 +181,59 @@ pub fn cmd_upgrade() -> Result<()> {
     Ok(())
 }
 
-/// Replace the destination binary (`dst`) atomically with `src`.
+/// Append `.bak` to `path`'s file name, e.g. `rz` -> `rz.bak`, `rz.exe` -> `rz.exe.bak`.
+fn backup_path(path: &Path) -> std::path::PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Self-verify a freshly installed `rz` binary by running `<path> --version`
+/// and checking it exits successfully and prints something that looks like a
+/// version string.
+///
+/// # Errors
+/// - Returns an error if the binary cannot be executed, exits non-zero, or
+///   its `--version` output doesn't contain a dotted, digit-led token.
+fn verify_binary(path: &Path) -> Result<()> {
+    let output = std::process::Command::new(path)
+        .arg("--version")
+        .output()
+        .with_context(|| format!("failed to execute {}", path.display()))?;
+    if !output.status.success() {
+        bail!(
+            "{} --version exited with {}",
+            path.display(),
+            output.status
+        );
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let looks_like_version = stdout.split_whitespace().any(|tok| {
+        tok.contains('.') && tok.chars().any(|c| c.is_ascii_digit())
+    });
+    if !looks_like_version {
+        bail!(
+            "{} --version did not print a parseable version: {stdout:?}",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Replace the destination binary (`dst`) atomically with `src`, keeping a
+/// rollback backup and self-verifying the new binary (rustup-style safe-swap).
 ///
 /// Steps:
-/// - Copy `src` to a temporary file `<dst>.new`.
-/// - Mark it as executable.
+/// - Copy `src` to a temporary file `<dst>.new` and mark it executable.
 /// - If `dst` already exists:
-///   - Compare SHA-256 of old and new binaries.
-///   - If hashes match → remove temp file and print "already up-to-date".
-/// - Otherwise, rename temp file to overwrite `dst`.
+///   - Compare SHA-256 of old and new binaries; if they match, remove the
+///     temp file and report "unchanged".
+///   - Otherwise, move `dst` to `<dst>.bak` (not delete it) before swapping in
+///     the new binary, so a bad release can always be undone.
+/// - Rename the temp file over `dst`.
+/// - Run `dst --version` to self-verify the freshly installed binary. On
+///   failure, restore `<dst>.bak` over `dst` and return an error instead of
+///   leaving a broken binary in place.
 fn atomic_replace(src: &Path, dst: &Path) -> Result<ReplaceOutcome> {
     let tmp_dst = dst.with_extension("new");
     if tmp_dst.exists() {
@@ -109,6 +241,8 @@ fn atomic_replace(src: &Path, dst: &Path) -> Result<ReplaceOutcome> {
     }
     fs::copy(src, &tmp_dst)?;
     make_executable(&tmp_dst)?;
+
+    let bak = backup_path(dst);
     if dst.exists() {
         let old = sha256_file(dst).unwrap_or_default();
         let new = sha256_file(&tmp_dst).unwrap_or_default();
@@ -116,7 +250,40 @@ fn atomic_replace(src: &Path, dst: &Path) -> Result<ReplaceOutcome> {
             let _ = fs::remove_file(&tmp_dst);
             return Ok(ReplaceOutcome::Unchanged);
         }
+        if bak.exists() {
+            let _ = fs::remove_file(&bak);
+        }
+        fs::rename(dst, &bak)?;
     }
     fs::rename(&tmp_dst, dst)?;
+
+    if let Err(e) = verify_binary(dst) {
+        if bak.exists() {
+            let _ = fs::remove_file(dst);
+            fs::rename(&bak, dst)?;
+        }
+        bail!("new rz binary failed self-verification, rolled back to previous version: {e}");
+    }
+
     Ok(ReplaceOutcome::Replaced)
 }
+
+/// Swap the most recent `<bin>.bak` backup (see [`atomic_replace`]) back into
+/// place, as a manual escape hatch from a bad release.
+///
+/// # Errors
+/// - Returns an error if no backup is present.
+pub fn cmd_upgrade_rollback() -> Result<()> {
+    let p = paths()?;
+    let target_bin = target_bin_path(&p.bin);
+    let bak = backup_path(&target_bin);
+    if !bak.exists() {
+        bail!("no rollback backup found at {}", bak.display());
+    }
+    if target_bin.exists() {
+        fs::remove_file(&target_bin)?;
+    }
+    fs::rename(&bak, &target_bin)?;
+    println!("rolled back to {}", target_bin.display());
+    Ok(())
+}
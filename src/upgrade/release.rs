@@ -0,0 +1,176 @@
+use anyhow::Result;
+use reqwest::blocking::Client;
+use serde::Deserialize;
+
+/// A release's tag and downloadable assets, normalized across hosts.
+#[derive(Debug, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub assets: Vec<Asset>,
+}
+
+/// A single release asset: its filename and direct download URL.
+#[derive(Debug, Deserialize)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+/// A pluggable source of releases for `rz upgrade`.
+///
+/// Each implementation owns its own HTTP client (hosts disagree on auth
+/// headers and required `Accept`/`User-Agent` values) and knows how to map
+/// its host's release JSON shape onto the common [`Release`]/[`Asset`]
+/// representation.
+pub trait ReleaseSource {
+    /// Build an HTTP client configured for this host (headers, auth token).
+    fn client(&self) -> Result<Client>;
+
+    /// Fetch metadata for the latest release: tag name and asset list.
+    fn fetch_latest(&self, client: &Client) -> Result<Release>;
+}
+
+/// Find a companion checksums asset for `asset_name`/`tag`, if the release
+/// publishes one.
+///
+/// Tries, in order:
+/// - `rz-<tag>-SHA256SUMS`, a single file covering every asset
+/// - `<asset_name>.sha256`, a per-asset checksum file
+pub fn find_checksum_asset<'a>(rel: &'a Release, asset_name: &str, tag: &str) -> Option<&'a Asset> {
+    let sums_name = format!("rz-{}-SHA256SUMS", tag);
+    let per_asset_name = format!("{}.sha256", asset_name);
+    rel.assets
+        .iter()
+        .find(|a| a.name == sums_name)
+        .or_else(|| rel.assets.iter().find(|a| a.name == per_asset_name))
+}
+
+/// Parse a `sha256sum`-style checksums file and find the digest for `filename`.
+///
+/// Each line looks like `"<hexdigest>  <filename>"` (one or more spaces,
+/// optionally a leading `*` on the filename for binary mode). Returns
+/// `None` if no line matches.
+pub fn parse_checksum_for_file(sums_text: &str, filename: &str) -> Option<String> {
+    for line in sums_text.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut parts = line.splitn(2, char::is_whitespace);
+        let (Some(digest), Some(name)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        if name.trim().trim_start_matches('*') == filename {
+            return Some(digest.to_string());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_checksum_asset_prefers_sums_file_over_per_asset() {
+        let rel = Release {
+            tag_name: "v0.1.2".to_string(),
+            assets: vec![
+                Asset {
+                    name: "rz-v0.1.2-linux-x86_64.tar.gz".to_string(),
+                    browser_download_url: "https://example.com/asset".to_string(),
+                },
+                Asset {
+                    name: "rz-v0.1.2-linux-x86_64.tar.gz.sha256".to_string(),
+                    browser_download_url: "https://example.com/per-asset".to_string(),
+                },
+                Asset {
+                    name: "rz-v0.1.2-SHA256SUMS".to_string(),
+                    browser_download_url: "https://example.com/sums".to_string(),
+                },
+            ],
+        };
+
+        let found = find_checksum_asset(&rel, "rz-v0.1.2-linux-x86_64.tar.gz", "v0.1.2").unwrap();
+        assert_eq!(found.name, "rz-v0.1.2-SHA256SUMS");
+    }
+
+    #[test]
+    fn find_checksum_asset_falls_back_to_per_asset_file() {
+        let rel = Release {
+            tag_name: "v0.1.2".to_string(),
+            assets: vec![
+                Asset {
+                    name: "rz-v0.1.2-linux-x86_64.tar.gz".to_string(),
+                    browser_download_url: "https://example.com/asset".to_string(),
+                },
+                Asset {
+                    name: "rz-v0.1.2-linux-x86_64.tar.gz.sha256".to_string(),
+                    browser_download_url: "https://example.com/per-asset".to_string(),
+                },
+            ],
+        };
+
+        let found = find_checksum_asset(&rel, "rz-v0.1.2-linux-x86_64.tar.gz", "v0.1.2").unwrap();
+        assert_eq!(found.name, "rz-v0.1.2-linux-x86_64.tar.gz.sha256");
+    }
+
+    #[test]
+    fn find_checksum_asset_none_when_absent() {
+        let rel = Release {
+            tag_name: "v0.1.2".to_string(),
+            assets: vec![Asset {
+                name: "rz-v0.1.2-linux-x86_64.tar.gz".to_string(),
+                browser_download_url: "https://example.com/asset".to_string(),
+            }],
+        };
+
+        assert!(find_checksum_asset(&rel, "rz-v0.1.2-linux-x86_64.tar.gz", "v0.1.2").is_none());
+    }
+
+    #[test]
+    fn parse_checksum_for_file_finds_matching_line() {
+        let sums = "deadbeef00000000000000000000000000000000000000000000000000000000  rz-v0.1.2-macos-aarch64.tar.gz\nabc123  rz-v0.1.2-linux-x86_64.tar.gz\n";
+        assert_eq!(
+            parse_checksum_for_file(sums, "rz-v0.1.2-linux-x86_64.tar.gz"),
+            Some("abc123".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_checksum_for_file_handles_binary_mode_marker() {
+        let sums = "abc123 *rz-v0.1.2-linux-x86_64.tar.gz\n";
+        assert_eq!(
+            parse_checksum_for_file(sums, "rz-v0.1.2-linux-x86_64.tar.gz"),
+            Some("abc123".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_checksum_for_file_none_when_no_match() {
+        let sums = "abc123  rz-v0.1.2-macos-aarch64.tar.gz\n";
+        assert_eq!(
+            parse_checksum_for_file(sums, "rz-v0.1.2-linux-x86_64.tar.gz"),
+            None
+        );
+    }
+
+    #[test]
+    fn release_struct_deserializes_from_github_like_json() {
+        let json = r#"
+{
+    "tag_name": "v1.2.3",
+    "assets": [
+        {
+            "name": "rz-v1.2.3-macos-aarch64.tar.gz",
+            "browser_download_url": "https://example.com/rz-v1.2.3-macos-aarch64.tar.gz"
+        }
+    ]
+}"#;
+
+        let rel: Release = serde_json::from_str(json).expect("deserialize");
+        assert_eq!(rel.tag_name, "v1.2.3");
+        assert_eq!(rel.assets.len(), 1);
+        assert_eq!(rel.assets[0].name, "rz-v1.2.3-macos-aarch64.tar.gz");
+    }
+}